@@ -1,4 +1,9 @@
-use std::{collections::HashMap, io::stdin, str::FromStr};
+use advent_of_code_2023::node_pattern::{Endpoints, NodePattern};
+use std::{
+    collections::{HashMap, HashSet},
+    io::stdin,
+    str::FromStr,
+};
 
 enum Direction {
     Left,
@@ -23,8 +28,9 @@ impl FromStr for Directions {
 }
 
 impl Directions {
-    fn iter(&self) -> impl Iterator<Item = &Direction> {
-        self.0.iter().cycle()
+    /// Repeats the directions forever, along with each one's index.
+    fn iter(&self) -> impl Iterator<Item = (usize, &Direction)> {
+        self.0.iter().enumerate().cycle()
     }
 }
 
@@ -39,9 +45,9 @@ impl FromStr for Edges {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let (left, right) = s
-            .strip_prefix("(")
+            .strip_prefix('(')
             .unwrap()
-            .strip_suffix(")")
+            .strip_suffix(')')
             .unwrap()
             .split_once(", ")
             .unwrap();
@@ -59,7 +65,10 @@ struct Input {
 
 impl Input {
     fn parse() -> Self {
-        let mut lines = stdin().lines().map(|line| line.unwrap());
+        Self::from_lines(stdin().lines().map(|line| line.unwrap()))
+    }
+
+    fn from_lines(mut lines: impl Iterator<Item = String>) -> Self {
         let directions = lines.next().unwrap().parse().unwrap();
         lines.next();
         let graph = lines
@@ -72,22 +81,80 @@ impl Input {
         Self { directions, graph }
     }
 
-    fn solve(&self) -> usize {
-        let mut node = "AAA";
-        for (i, direction) in self.directions.iter().enumerate() {
-            if node == "ZZZ" {
-                return i;
+    /// Follows the directions from the single start node, failing once a
+    /// (node, direction index) state repeats since no goal can then be reached.
+    fn solve(&self, start: &NodePattern, goal: &NodePattern) -> Result<usize, String> {
+        let starts = self
+            .graph
+            .keys()
+            .filter(|node| start.matches(node))
+            .collect::<Vec<_>>();
+        let [mut node] = starts[..] else {
+            return Err(format!("Expected one start node, found {starts:?}"));
+        };
+        let mut seen = HashSet::new();
+        for (step, (i, direction)) in self.directions.iter().enumerate() {
+            if goal.matches(node) {
+                return Ok(step);
+            }
+            if !seen.insert((node, i)) {
+                return Err(format!(
+                    "No goal is reachable: node {node} at direction {i} repeats after {step} steps"
+                ));
             }
-            let edges = self.graph.get(node).unwrap();
+            let edges = self
+                .graph
+                .get(node)
+                .ok_or_else(|| format!("Node {node} is not in the network"))?;
             node = match direction {
                 Direction::Left => &edges.left,
                 Direction::Right => &edges.right,
             }
         }
-        0
+        Err("No directions given".to_owned())
     }
 }
 
+#[test]
+fn test_solve() {
+    let input = |lines: &[&str]| Input::from_lines(lines.iter().map(|line| (*line).to_owned()));
+    let (start, goal) = (
+        NodePattern::Exact("AAA".to_owned()),
+        NodePattern::Exact("ZZZ".to_owned()),
+    );
+    let rl = input(&[
+        "RL",
+        "",
+        "AAA = (BBB, CCC)",
+        "BBB = (DDD, EEE)",
+        "CCC = (ZZZ, GGG)",
+        "DDD = (DDD, DDD)",
+        "EEE = (EEE, EEE)",
+        "GGG = (GGG, GGG)",
+        "ZZZ = (ZZZ, ZZZ)",
+    ]);
+    assert_eq!(rl.solve(&start, &goal), Ok(2));
+    let llr = input(&[
+        "LLR",
+        "",
+        "AAA = (BBB, BBB)",
+        "BBB = (AAA, ZZZ)",
+        "ZZZ = (ZZZ, ZZZ)",
+    ]);
+    assert_eq!(llr.solve(&start, &goal), Ok(6));
+    let looping = input(&["L", "", "AAA = (BBB, BBB)", "BBB = (AAA, AAA)"]);
+    assert!(looping
+        .solve(&start, &goal)
+        .is_err_and(|error| error.starts_with("No goal is reachable")));
+}
+
 fn main() {
-    println!("{}", Input::parse().solve())
+    let endpoints = Endpoints::parse(
+        NodePattern::Exact("AAA".to_owned()),
+        NodePattern::Exact("ZZZ".to_owned()),
+    );
+    match Input::parse().solve(&endpoints.start, &endpoints.goal) {
+        Ok(answer) => println!("{answer}"),
+        Err(error) => panic!("{error}"),
+    }
 }
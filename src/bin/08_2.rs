@@ -1,11 +1,13 @@
+use advent_of_code_2023::node_pattern::{Endpoints, NodePattern};
 use std::{
     collections::{BTreeSet, HashMap},
+    fmt::{self, Display, Formatter},
     io::stdin,
     str::FromStr,
 };
 
 use itertools::Itertools;
-use num::integer::lcm;
+use num::integer::{ExtendedGcd, Integer};
 
 enum Direction {
     Left,
@@ -40,9 +42,9 @@ impl FromStr for Edges {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let (left, right) = s
-            .strip_prefix("(")
+            .strip_prefix('(')
             .unwrap()
-            .strip_suffix(")")
+            .strip_suffix(')')
             .unwrap()
             .split_once(", ")
             .unwrap();
@@ -61,115 +63,152 @@ struct Cycle {
     hits_in_cycle: BTreeSet<usize>,
 }
 
+impl Display for Cycle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "pre-cycle length {}, period {}, hits before cycle {:?}, hit offsets in cycle {:?}",
+            self.start, self.period, self.hits_before_cycle, self.hits_in_cycle
+        )
+    }
+}
+
 impl Cycle {
-    fn increase_start(self, n: usize) -> Self {
-        let start = self.start + n;
-        let mut new_hits_before_cycle = self.hits_in_cycle;
-        let mut hits_in_cycle = new_hits_before_cycle.split_off(&n);
-        hits_in_cycle.extend(new_hits_before_cycle.iter().map(|hit| hit + self.period));
-        let hits_in_cycle = hits_in_cycle.into_iter().map(|hit| hit - n).collect();
-        let mut hits_before_cycle = self.hits_before_cycle;
-        hits_before_cycle.extend(new_hits_before_cycle.iter().map(|hit| hit + self.start));
-        Self {
-            start,
-            hits_before_cycle,
-            period: self.period,
-            hits_in_cycle,
+    fn hits(&self) -> Hits {
+        Hits {
+            finite: self.hits_before_cycle.clone(),
+            classes: self
+                .hits_in_cycle
+                .iter()
+                .map(|hit| Class {
+                    residue: (self.start + hit) % self.period,
+                    modulus: self.period,
+                    min: self.start + hit,
+                })
+                .collect(),
         }
     }
+}
 
-    fn multiply_cycle(self, n: usize) -> Self {
-        let period = self.period * n;
-        let hits_in_cycle = (0..n)
-            .flat_map(|i| {
-                self.hits_in_cycle
-                    .iter()
-                    .map(|hit| hit + i * self.period)
-                    .collect_vec()
-            })
-            .collect();
-        Self {
-            start: self.start,
-            hits_before_cycle: self.hits_before_cycle,
-            period,
-            hits_in_cycle,
-        }
+/// The times `t >= min` with `t % modulus == residue`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Class {
+    residue: usize,
+    modulus: usize,
+    min: usize,
+}
+
+impl Class {
+    fn contains(&self, t: usize) -> bool {
+        t >= self.min && t % self.modulus == self.residue
     }
 
     fn first(&self) -> usize {
-        self.hits_before_cycle
-            .first()
-            .cloned()
-            .unwrap_or_else(|| self.hits_in_cycle.first().unwrap() + self.start)
+        self.min + (self.residue + self.modulus - self.min % self.modulus) % self.modulus
     }
 
-    fn intersect(a: Self, b: Self) -> Self {
-        let (a_start, b_start, a_period, b_period) = (a.start, b.start, a.period, b.period);
-        let start = a.start.max(b.start);
-        let (a, b) = (
-            a.increase_start(start - a_start),
-            b.increase_start(start - b_start),
-        );
-        let period = lcm(a.period, b.period);
-        let (a, b) = (
-            a.multiply_cycle(period / a_period),
-            b.multiply_cycle(period / b_period),
-        );
-        let hits_before_cycle = a
-            .hits_before_cycle
-            .intersection(&b.hits_before_cycle)
-            .cloned()
-            .collect();
-        let hits_in_cycle = a
-            .hits_in_cycle
-            .intersection(&b.hits_in_cycle)
-            .cloned()
-            .collect();
-        Self {
-            start,
-            hits_before_cycle,
-            period,
-            hits_in_cycle,
+    /// Combines two classes with the Chinese remainder theorem, allowing
+    /// non-coprime moduli.
+    fn intersect(a: &Self, b: &Self) -> Option<Self> {
+        let (m1, m2) = (a.modulus as i128, b.modulus as i128);
+        let ExtendedGcd { gcd, x, .. } = m1.extended_gcd(&m2);
+        let difference = b.residue as i128 - a.residue as i128;
+        if difference % gcd != 0 {
+            return None;
         }
+        let modulus = m1 / gcd * m2;
+        let k = (difference / gcd * x).rem_euclid(m2 / gcd);
+        let residue = (a.residue as i128 + m1 * k).rem_euclid(modulus);
+        Some(Self {
+            residue: residue as usize,
+            modulus: modulus as usize,
+            min: a.min.max(b.min),
+        })
     }
 }
 
 #[test]
-fn test_increase_start() {
-    let cycle = Cycle {
-        start: 3,
-        hits_before_cycle: vec![0, 2].into_iter().collect(),
-        period: 5,
-        hits_in_cycle: vec![0, 2, 4].into_iter().collect(),
+fn test_class_intersect() {
+    let a = Class {
+        residue: 2,
+        modulus: 4,
+        min: 2,
+    };
+    let b = Class {
+        residue: 4,
+        modulus: 6,
+        min: 10,
+    };
+    let c = Class {
+        residue: 1,
+        modulus: 6,
+        min: 0,
     };
     assert_eq!(
-        cycle.increase_start(1),
-        Cycle {
-            start: 4,
-            hits_before_cycle: vec![0, 2, 3].into_iter().collect(),
-            period: 5,
-            hits_in_cycle: vec![1, 3, 4].into_iter().collect(),
-        }
+        Class::intersect(&a, &b),
+        Some(Class {
+            residue: 10,
+            modulus: 12,
+            min: 10,
+        })
     );
+    assert_eq!(Class::intersect(&a, &c), None);
+    assert_eq!(Class::intersect(&a, &b).unwrap().first(), 10);
+}
+
+/// Every time at which a ghost (or all of a set of ghosts) is on a goal node.
+#[derive(Debug, PartialEq, Eq)]
+struct Hits {
+    finite: BTreeSet<usize>,
+    classes: Vec<Class>,
+}
+
+impl Hits {
+    fn contains(&self, t: usize) -> bool {
+        self.finite.contains(&t) || self.classes.iter().any(|class| class.contains(t))
+    }
+
+    fn first(&self) -> Option<usize> {
+        self.finite
+            .iter()
+            .copied()
+            .chain(self.classes.iter().map(Class::first))
+            .min()
+    }
+
+    fn intersect(a: Self, b: Self) -> Self {
+        let finite = a
+            .finite
+            .iter()
+            .copied()
+            .filter(|&t| b.contains(t))
+            .chain(b.finite.iter().copied().filter(|&t| a.contains(t)))
+            .collect();
+        let classes = a
+            .classes
+            .iter()
+            .cartesian_product(&b.classes)
+            .filter_map(|(a, b)| Class::intersect(a, b))
+            .unique()
+            .collect();
+        Self { finite, classes }
+    }
 }
 
 #[test]
-fn test_multiply_cycle() {
-    let cycle = Cycle {
-        start: 3,
-        hits_before_cycle: vec![0, 2].into_iter().collect(),
-        period: 5,
-        hits_in_cycle: vec![0, 2, 4].into_iter().collect(),
+fn test_hits_intersect() {
+    let cycle = |start, hits_before_cycle: Vec<usize>, period, hits_in_cycle: Vec<usize>| Cycle {
+        start,
+        hits_before_cycle: hits_before_cycle.into_iter().collect(),
+        period,
+        hits_in_cycle: hits_in_cycle.into_iter().collect(),
     };
-    assert_eq!(
-        cycle.multiply_cycle(2),
-        Cycle {
-            start: 3,
-            hits_before_cycle: vec![0, 2].into_iter().collect(),
-            period: 10,
-            hits_in_cycle: vec![0, 2, 4, 5, 7, 9].into_iter().collect(),
-        }
-    );
+    let a = cycle(3, vec![1], 5, vec![2]);
+    let b = cycle(1, vec![], 3, vec![0, 2]);
+    let hits = Hits::intersect(a.hits(), b.hits());
+    assert_eq!(hits.first(), Some(1));
+    assert!(hits.contains(10) && hits.contains(15) && hits.contains(25));
+    assert!(!hits.contains(5) && !hits.contains(20));
 }
 
 struct Input {
@@ -192,7 +231,7 @@ impl Input {
         Self { directions, graph }
     }
 
-    fn cycle_from(&self, start: Node) -> Cycle {
+    fn cycle_from(&self, start: Node, goal: &NodePattern) -> Cycle {
         let mut seen: HashMap<(usize, String), usize> = HashMap::new();
         let mut hits = BTreeSet::new();
         let mut node = start;
@@ -212,7 +251,7 @@ impl Input {
                     hits_in_cycle,
                 };
             }
-            if node.ends_with('Z') {
+            if goal.matches(&node) {
                 hits.insert(overall_i);
             }
             node = match direction {
@@ -223,17 +262,28 @@ impl Input {
         unreachable!()
     }
 
-    fn solve(&self) -> usize {
+    fn solve(&self, start: &NodePattern, goal: &NodePattern) -> Option<usize> {
         self.graph
             .keys()
-            .filter(|node| node.ends_with("A"))
-            .map(|node| self.cycle_from(node.clone()))
-            .reduce(|a, b| Cycle::intersect(a, b))
-            .unwrap()
+            .filter(|node| start.matches(node))
+            .sorted()
+            .map(|node| {
+                let cycle = self.cycle_from(node.clone(), goal);
+                eprintln!("{node}: {cycle}");
+                cycle.hits()
+            })
+            .reduce(Hits::intersect)?
             .first()
     }
 }
 
 fn main() {
-    println!("{}", Input::parse().solve())
+    let endpoints = Endpoints::parse(
+        NodePattern::Suffix("A".to_owned()),
+        NodePattern::Suffix("Z".to_owned()),
+    );
+    match Input::parse().solve(&endpoints.start, &endpoints.goal) {
+        Some(answer) => println!("{answer}"),
+        None => panic!("The ghosts are never all on goal nodes at once"),
+    }
 }
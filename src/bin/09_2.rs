@@ -1,31 +1,6 @@
-use itertools::Itertools;
-use std::{io::stdin, str::FromStr};
-
-struct History(Vec<i64>);
-
-impl FromStr for History {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self(
-            s.split_whitespace().map(|s| s.parse().unwrap()).collect(),
-        ))
-    }
-}
-
-impl History {
-    fn derivative(&self) -> Self {
-        Self(self.0.iter().tuple_windows().map(|(a, b)| b - a).collect())
-    }
-
-    fn prev(&self) -> i64 {
-        if self.0.iter().all(|v| *v == 0) {
-            0
-        } else {
-            *self.0.first().unwrap() - self.derivative().prev()
-        }
-    }
-}
+use advent_of_code_2023::history::{total_extrapolated, History, Options};
+use num::BigInt;
+use std::io::stdin;
 
 struct Input(Vec<History>);
 
@@ -39,11 +14,21 @@ impl Input {
         )
     }
 
-    fn solve(&self) -> i64 {
-        self.0.iter().map(|history| history.prev()).sum()
+    fn solve(&self, steps: i64) -> Result<BigInt, String> {
+        total_extrapolated(&self.0, steps)
     }
 }
 
 fn main() {
-    println!("{}", Input::parse().solve())
+    let options = Options::parse(-1);
+    let input = Input::parse();
+    if options.table {
+        for history in &input.0 {
+            eprintln!("{}", history.render_table());
+        }
+    }
+    match input.solve(options.steps) {
+        Ok(answer) => println!("{answer}"),
+        Err(error) => panic!("{error}"),
+    }
 }
@@ -1,4 +1,9 @@
-use std::{collections::HashMap, io::stdin};
+use advent_of_code_2023::{bitgrid::BitGrid, cycle::Cycle};
+use std::{
+    fmt::{self, Display, Formatter},
+    io::stdin,
+    str::FromStr,
+};
 
 #[derive(PartialEq, Eq, Hash, Clone)]
 enum Rock {
@@ -6,16 +11,50 @@ enum Rock {
     Cube,
 }
 
+#[derive(Clone, Copy, Debug)]
+enum Direction {
+    North,
+    West,
+    South,
+    East,
+}
+
+impl Direction {
+    const SPIN_CYCLE: [Direction; 4] = [
+        Direction::North,
+        Direction::West,
+        Direction::South,
+        Direction::East,
+    ];
+}
+
+impl FromStr for Direction {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "north" => Ok(Direction::North),
+            "west" => Ok(Direction::West),
+            "south" => Ok(Direction::South),
+            "east" => Ok(Direction::East),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
 struct Input(Vec<Vec<Option<Rock>>>);
 
 impl Input {
     fn parse() -> Self {
+        Self::from_lines(stdin().lines().map(|line| line.unwrap()))
+    }
+
+    fn from_lines(lines: impl Iterator<Item = String>) -> Self {
         Self(
-            stdin()
-                .lines()
+            lines
                 .map(|line| {
-                    line.unwrap()
-                        .chars()
+                    line.chars()
                         .map(|c| match c {
                             'O' => Some(Rock::Round),
                             '#' => Some(Rock::Cube),
@@ -27,117 +66,200 @@ impl Input {
         )
     }
 
-    fn tilt_north(&mut self) {
-        loop {
-            let mut changed = false;
-            for r in 1..self.0.len() {
-                for c in 0..self.0[r].len() {
-                    if let (None, Some(Rock::Round)) = (&self.0[r - 1][c], &self.0[r][c]) {
-                        self.0[r - 1][c] = Some(Rock::Round);
+    /// Slides every round rock towards `direction` in one pass per line,
+    /// tracking where the next rock in the line will come to rest.
+    fn tilt(&mut self, direction: Direction) {
+        let (height, width) = (self.0.len(), self.0[0].len());
+        let (lines, length) = match direction {
+            Direction::North | Direction::South => (width, height),
+            Direction::West | Direction::East => (height, width),
+        };
+        for line in 0..lines {
+            let position = |k: usize| match direction {
+                Direction::North => (k, line),
+                Direction::South => (height - 1 - k, line),
+                Direction::West => (line, k),
+                Direction::East => (line, width - 1 - k),
+            };
+            let mut resting = 0;
+            for k in 0..length {
+                let (r, c) = position(k);
+                match self.0[r][c] {
+                    Some(Rock::Cube) => resting = k + 1,
+                    Some(Rock::Round) => {
                         self.0[r][c] = None;
-                        changed = true;
+                        let (r, c) = position(resting);
+                        self.0[r][c] = Some(Rock::Round);
+                        resting += 1;
                     }
+                    None => {}
                 }
             }
-            if !changed {
-                return;
-            }
         }
     }
 
-    fn tilt_west(&mut self) {
-        loop {
-            let mut changed = false;
-            for r in 0..self.0.len() {
-                for c in 1..self.0[r].len() {
-                    if let (None, Some(Rock::Round)) = (&self.0[r][c - 1], &self.0[r][c]) {
-                        self.0[r][c - 1] = Some(Rock::Round);
-                        self.0[r][c] = None;
-                        changed = true;
-                    }
-                }
-            }
-            if !changed {
-                return;
-            }
+    fn spin_cycle(&mut self) {
+        for direction in Direction::SPIN_CYCLE {
+            self.tilt(direction);
         }
     }
 
-    fn tilt_south(&mut self) {
-        loop {
-            let mut changed = false;
-            for r in (0..self.0.len() - 1).rev() {
-                for c in 0..self.0[r].len() {
-                    if let (None, Some(Rock::Round)) = (&self.0[r + 1][c], &self.0[r][c]) {
-                        self.0[r + 1][c] = Some(Rock::Round);
-                        self.0[r][c] = None;
-                        changed = true;
-                    }
-                }
-            }
-            if !changed {
-                return;
+    /// The total load on the `side` support beams: each round rock weighs
+    /// its distance in rows or columns from the opposite edge, inclusive.
+    fn load(&self, side: Direction) -> usize {
+        let (height, width) = (self.0.len(), self.0[0].len());
+        self.0
+            .iter()
+            .enumerate()
+            .flat_map(|(r, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(_, cell)| matches!(cell, Some(Rock::Round)))
+                    .map(move |(c, _)| match side {
+                        Direction::North => height - r,
+                        Direction::South => r + 1,
+                        Direction::West => width - c,
+                        Direction::East => c + 1,
+                    })
+            })
+            .sum()
+    }
+
+    /// Prints the platform after every tilt of the first `spin_cycles` spin
+    /// cycles.
+    fn print_frames(&self, spin_cycles: usize) {
+        let mut platform = self.clone();
+        for cycle in 1..=spin_cycles {
+            for direction in Direction::SPIN_CYCLE {
+                platform.tilt(direction);
+                eprintln!("Cycle {cycle}, after tilting {direction:?}:\n{platform}");
             }
         }
     }
 
-    fn tilt_east(&mut self) {
-        loop {
-            let mut changed = false;
-            for r in 0..self.0.len() {
-                for c in (0..self.0[r].len() - 1).rev() {
-                    if let (None, Some(Rock::Round)) = (&self.0[r][c + 1], &self.0[r][c]) {
-                        self.0[r][c + 1] = Some(Rock::Round);
-                        self.0[r][c] = None;
-                        changed = true;
-                    }
-                }
-            }
-            if !changed {
-                return;
+    /// The positions of the round rocks, which are all that change between
+    /// spin cycles.
+    fn round_rocks(&self) -> BitGrid {
+        let mut rocks = BitGrid::new(self.0[0].len(), self.0.len());
+        for (r, row) in self.0.iter().enumerate() {
+            for (c, cell) in row.iter().enumerate() {
+                rocks.set(r, c, *cell == Some(Rock::Round));
             }
         }
+        rocks
     }
 
-    fn spin_cycle(&mut self) {
-        self.tilt_north();
-        self.tilt_west();
-        self.tilt_south();
-        self.tilt_east();
+    fn set_round_rocks(&mut self, rocks: &BitGrid) {
+        for (r, row) in self.0.iter_mut().enumerate() {
+            for (c, cell) in row.iter_mut().enumerate() {
+                if *cell != Some(Rock::Cube) {
+                    *cell = rocks.get(r, c).then_some(Rock::Round);
+                }
+            }
+        }
     }
 
-    fn north_load(&self) -> usize {
-        let height = self.0.len();
-        self.0
-            .iter()
-            .enumerate()
-            .map(|(i, row)| {
-                row.into_iter()
-                    .filter(|cell| matches!(cell, Some(Rock::Round)))
-                    .count()
-                    * (height - i)
-            })
-            .sum()
+    /// Finds the spin-cycle period on packed round-rock positions, spinning
+    /// one scratch platform rather than cloning the grid for every state.
+    fn solve(&self, spin_cycles: usize, side: Direction) -> usize {
+        let mut scratch = self.clone();
+        let step = |rocks: &BitGrid| {
+            scratch.set_round_rocks(rocks);
+            scratch.spin_cycle();
+            scratch.round_rocks()
+        };
+        let cycle = Cycle::find(&self.round_rocks(), step);
+        let mut platform = self.clone();
+        for _ in 0..cycle.equivalent_steps(spin_cycles) {
+            platform.spin_cycle();
+        }
+        platform.load(side)
     }
+}
 
-    fn solve(&mut self) -> usize {
-        let mut seen: HashMap<Vec<Vec<Option<Rock>>>, usize> = HashMap::new();
-        loop {
-            if let Some(j) = seen.get(&self.0) {
-                let period = seen.len() - j;
-                let final_offset = (1000000000 - seen.len()) % period;
-                for _ in 0..final_offset {
-                    self.spin_cycle();
-                }
-                return self.north_load();
-            } else {
-                seen.insert(self.0.clone(), seen.len());
-                self.spin_cycle();
+impl Display for Input {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for row in &self.0 {
+            for cell in row {
+                let c = match cell {
+                    Some(Rock::Round) => 'O',
+                    Some(Rock::Cube) => '#',
+                    None => '.',
+                };
+                write!(f, "{c}")?;
             }
+            writeln!(f)?;
         }
+        Ok(())
     }
 }
 
+#[cfg(test)]
+fn platform(rows: &str) -> Input {
+    Input::from_lines(rows.split_whitespace().map(str::to_owned))
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "
+    O....#.... O.OO#....# .....##... OO.#O....O .O.....O#.
+    O.#..O.#.# ..O..#O..O .......O.. #....###.. #OO..#....
+";
+
+#[test]
+fn test_tilt() {
+    let mut tilted = platform(EXAMPLE);
+    tilted.tilt(Direction::North);
+    assert_eq!(tilted.load(Direction::North), 136);
+    let mut spun = platform(EXAMPLE);
+    for expected in [
+        ".....#.... ....#...O# ...OO##... .OO#...... .....OOO#.
+         .O#...O#.# ....O#.... ......OOOO #...O###.. #..OO#....",
+        ".....#.... ....#...O# .....##... ..O#...... .....OOO#.
+         .O#...O#.# ....O#...O .......OOO #..OO###.. #.OOO#...O",
+        ".....#.... ....#...O# .....##... ..O#...... .....OOO#.
+         .O#...O#.# ....O#...O .......OOO #...O###.O #.OOO#...O",
+    ] {
+        spun.spin_cycle();
+        assert_eq!(spun.to_string(), platform(expected).to_string());
+    }
+}
+
+#[test]
+fn test_solve() {
+    let input = platform(EXAMPLE);
+    assert_eq!(input.solve(1000000000, Direction::North), 64);
+    assert_eq!(input.solve(1, Direction::North), 87);
+    assert_eq!(input.solve(0, Direction::North), 104);
+}
+
+#[test]
+fn test_load() {
+    let input = platform("OO. ..# O..");
+    let loads = Direction::SPIN_CYCLE.map(|side| input.load(side));
+    assert_eq!(loads, [7, 8, 5, 4]);
+}
+
 fn main() {
-    println!("{}", Input::parse().solve())
+    let mut spin_cycles = 1000000000;
+    let mut side = Direction::North;
+    let mut frames = 0;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--load" => {
+                let value = args.next().expect("--load needs a side");
+                side = value
+                    .parse()
+                    .expect("Side must be north, west, south or east");
+            }
+            "--frames" => {
+                let value = args.next().expect("--frames needs a count");
+                frames = value.parse().expect("Frame count must be an integer");
+            }
+            _ => spin_cycles = arg.parse().expect("Spin cycles must be an integer"),
+        }
+    }
+    let input = Input::parse();
+    input.print_frames(frames);
+    println!("{}", input.solve(spin_cycles, side))
 }
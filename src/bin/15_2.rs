@@ -1,4 +1,8 @@
-use std::{io::stdin, str::FromStr};
+use std::{
+    fmt::{self, Display, Formatter},
+    io::stdin,
+    str::FromStr,
+};
 
 struct Label(String);
 
@@ -37,6 +41,15 @@ impl FromStr for Step {
     }
 }
 
+impl Display for Step {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.operation {
+            Operation::Remove => write!(f, "{}-", self.label.0),
+            Operation::Upsert(focal_length) => write!(f, "{}={focal_length}", self.label.0),
+        }
+    }
+}
+
 struct Box(Vec<(String, usize)>);
 
 impl Box {
@@ -53,47 +66,135 @@ impl Box {
     }
 }
 
-struct Input(Vec<Step>);
+struct Boxes(Vec<Box>);
 
-impl Input {
-    fn parse() -> Self {
-        let line = stdin().lines().next().unwrap().unwrap();
-        Self(line.split(',').map(|s| s.parse().unwrap()).collect())
+impl Boxes {
+    fn new() -> Self {
+        Self((0..256).map(|_| Box(vec![])).collect())
     }
 
-    fn solve(self) -> usize {
-        let mut boxes: Vec<Box> = (0..256).map(|_| Box(vec![])).collect();
-        for step in self.0 {
-            let box_i = step.label.hash();
-            match step {
-                Step {
-                    label,
-                    operation: Operation::Remove,
-                } => {
-                    boxes[box_i].remove(&label.0);
-                }
-                Step {
-                    label,
-                    operation: Operation::Upsert(focal_length),
-                } => {
-                    boxes[box_i].upsert(&label.0, focal_length);
-                }
-            }
+    fn apply(&mut self, step: &Step) {
+        let box_ = &mut self.0[step.label.hash()];
+        match step.operation {
+            Operation::Remove => box_.remove(&step.label.0),
+            Operation::Upsert(focal_length) => box_.upsert(&step.label.0, focal_length),
         }
-        boxes
-            .into_iter()
+    }
+
+    /// The box and slot holding the lens labelled `label`, and its focal
+    /// length.
+    fn find(&self, label: &str) -> Option<(usize, usize, usize)> {
+        let box_i = Label(label.to_owned()).hash();
+        self.0[box_i]
+            .0
+            .iter()
+            .position(|(l, _)| l == label)
+            .map(|slot| (box_i, slot, self.0[box_i].0[slot].1))
+    }
+
+    fn focusing_power(&self) -> usize {
+        self.0
+            .iter()
             .enumerate()
             .flat_map(|(box_i, box_)| {
                 box_.0
-                    .into_iter()
+                    .iter()
                     .enumerate()
-                    .map(|(lens_i, (_, focal_length))| (box_i + 1) * (lens_i + 1) * focal_length)
-                    .collect::<Vec<_>>()
+                    .map(move |(lens_i, (_, focal_length))| {
+                        (box_i + 1) * (lens_i + 1) * focal_length
+                    })
             })
             .sum()
     }
 }
 
+/// Lists the non-empty boxes as in the puzzle text, e.g. `Box 3: [ot 7] [ab 5]`.
+impl Display for Boxes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (box_i, box_) in self.0.iter().enumerate() {
+            if box_.0.is_empty() {
+                continue;
+            }
+            write!(f, "Box {box_i}:")?;
+            for (label, focal_length) in &box_.0 {
+                write!(f, " [{label} {focal_length}]")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct Options {
+    /// Print the non-empty boxes after each step.
+    steps: bool,
+    /// Labels to look up once every step is done.
+    find: Vec<String>,
+}
+
+impl Options {
+    fn parse() -> Self {
+        let mut options = Self::default();
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--steps" => options.steps = true,
+                "--find" => options
+                    .find
+                    .push(args.next().expect("--find needs a label")),
+                _ => panic!("Usage: 15_2 [--steps] [--find LABEL]..."),
+            }
+        }
+        options
+    }
+}
+
+struct Input(Vec<Step>);
+
+impl Input {
+    fn parse() -> Self {
+        let line = stdin().lines().next().unwrap().unwrap();
+        Self(line.split(',').map(|s| s.parse().unwrap()).collect())
+    }
+
+    fn solve(&self, options: &Options) -> usize {
+        let mut boxes = Boxes::new();
+        for step in &self.0 {
+            boxes.apply(step);
+            if options.steps {
+                eprintln!("After \"{step}\":\n{boxes}");
+            }
+        }
+        for label in &options.find {
+            match boxes.find(label) {
+                Some((box_i, slot, focal_length)) => eprintln!(
+                    "{label}: box {box_i}, slot {}, focal length {focal_length}",
+                    slot + 1
+                ),
+                None => eprintln!("{label}: not in box {}", Label(label.clone()).hash()),
+            }
+        }
+        boxes.focusing_power()
+    }
+}
+
+#[test]
+fn test_boxes() {
+    let mut boxes = Boxes::new();
+    for step in "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7".split(',') {
+        boxes.apply(&step.parse().unwrap());
+    }
+    assert_eq!(
+        boxes.to_string(),
+        "Box 0: [rn 1] [cm 2]\nBox 3: [ot 7] [ab 5] [pc 6]\n"
+    );
+    assert_eq!(boxes.find("ab"), Some((3, 1, 5)));
+    assert_eq!(boxes.find("qp"), None);
+    assert_eq!(boxes.focusing_power(), 145);
+}
+
 fn main() {
-    println!("{}", Input::parse().solve())
+    let options = Options::parse();
+    println!("{}", Input::parse().solve(&options))
 }
@@ -1,8 +1,13 @@
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::{self, Display, Formatter},
     io::stdin,
+    ops::RangeInclusive,
+    str::FromStr,
 };
 
+use itertools::Itertools;
+
 struct Pulse {
     source: String,
     destination: String,
@@ -21,6 +26,7 @@ impl Pulse {
 
 struct PulseQueue {
     queue: VecDeque<Pulse>,
+    button_presses: usize,
     lows_sent: usize,
     highs_sent: usize,
 }
@@ -29,12 +35,16 @@ impl PulseQueue {
     fn new() -> Self {
         Self {
             queue: VecDeque::new(),
+            button_presses: 0,
             lows_sent: 0,
             highs_sent: 0,
         }
     }
 
     fn enqueue(&mut self, pulse: Pulse) {
+        if pulse.source == "button" {
+            self.button_presses += 1;
+        }
         if pulse.value {
             self.highs_sent += 1;
         } else {
@@ -46,9 +56,61 @@ impl PulseQueue {
     fn dequeue(&mut self) -> Option<Pulse> {
         self.queue.pop_front()
     }
+}
+
+/// Prints processed pulses in the puzzle's `a -high-> b` notation, prefixed by
+/// the button press they belong to. Empty filters match everything.
+#[derive(Default)]
+struct Trace {
+    enabled: bool,
+    modules: Vec<String>,
+    value: Option<bool>,
+    presses: Option<RangeInclusive<usize>>,
+}
+
+impl Trace {
+    /// Applies `arg` if it is a trace option, taking its value from `args`.
+    fn parse_arg(&mut self, arg: &str, args: &mut impl Iterator<Item = String>) -> bool {
+        let mut value = || args.next().unwrap_or_else(|| panic!("{arg} needs a value"));
+        match arg {
+            "--trace" => {}
+            "--trace-module" => self.modules.push(value()),
+            "--trace-value" => {
+                self.value = Some(match value().as_str() {
+                    "high" => true,
+                    "low" => false,
+                    other => panic!("Invalid pulse value {other}"),
+                })
+            }
+            "--trace-presses" => {
+                let range = value();
+                let (first, last) = range.split_once('-').expect("Expected FIRST-LAST");
+                self.presses = Some(first.parse().unwrap()..=last.parse().unwrap());
+            }
+            _ => return false,
+        }
+        self.enabled = true;
+        true
+    }
 
-    fn summary(&self) -> usize {
-        self.lows_sent * self.highs_sent
+    fn log(&self, pulse: &Pulse, button_presses: usize) {
+        if self.enabled
+            && (self.modules.is_empty()
+                || self.modules.contains(&pulse.source)
+                || self.modules.contains(&pulse.destination))
+            && self.value.is_none_or(|value| value == pulse.value)
+            && self
+                .presses
+                .as_ref()
+                .is_none_or(|presses| presses.contains(&button_presses))
+        {
+            eprintln!(
+                "{button_presses}: {} -{}-> {}",
+                pulse.source,
+                if pulse.value { "high" } else { "low" },
+                pulse.destination
+            );
+        }
     }
 }
 
@@ -83,9 +145,167 @@ struct Module {
     destinations: Vec<String>,
 }
 
+struct Counter {
+    start: String,
+    conjunction: String,
+    /// Least significant first; set where the flip-flop feeds `conjunction`.
+    bits: Vec<bool>,
+    /// Non-flip-flop modules fed by `conjunction`.
+    outputs: Vec<String>,
+    /// The conjunction combining this counter with the others.
+    final_conjunction: String,
+}
+
+impl Counter {
+    fn period(&self) -> usize {
+        self.bits
+            .iter()
+            .rev()
+            .fold(0, |acc, &bit| acc * 2 + bit as usize)
+    }
+}
+
+impl Display for Counter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let pattern: String = self
+            .bits
+            .iter()
+            .rev()
+            .map(|&bit| if bit { '1' } else { '0' })
+            .collect();
+        write!(f, "{} -> {}", self.start, self.conjunction)?;
+        if !self.outputs.is_empty() {
+            write!(f, " -> {}", self.outputs.join(", "))?;
+        }
+        write!(
+            f,
+            " -> ... -> {}: bits {pattern}, period {}",
+            self.final_conjunction,
+            self.period()
+        )
+    }
+}
+
+/// A suspicious piece of wiring found by `Modules::wiring_issues`.
+enum WiringIssue {
+    /// A destination that is not itself a module, such as `rx` or `output`.
+    Sink(String),
+    InputlessConjunction(String),
+    UnreachableFromBroadcaster(String),
+}
+
+impl WiringIssue {
+    /// Sinks are expected in most circuits, so only the other issues become
+    /// errors in strict mode.
+    fn is_error(&self, strict: bool) -> bool {
+        strict && !matches!(self, WiringIssue::Sink(_))
+    }
+}
+
+impl Display for WiringIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            WiringIssue::Sink(label) => write!(f, "{label} receives pulses but is not a module"),
+            WiringIssue::InputlessConjunction(label) => {
+                write!(f, "conjunction {label} has no inputs")
+            }
+            WiringIssue::UnreachableFromBroadcaster(label) => {
+                write!(f, "{label} is unreachable from broadcaster")
+            }
+        }
+    }
+}
+
 struct Modules(HashMap<String, Module>);
 
+/// The state of every flip-flop and conjunction memory, packed into bits.
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Snapshot(Vec<u64>);
+
+impl Display for Snapshot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for word in &self.0 {
+            write!(f, "{word:016x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Snapshot {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.is_ascii() || !s.len().is_multiple_of(16) {
+            return Err(());
+        }
+        (0..s.len())
+            .step_by(16)
+            .map(|i| u64::from_str_radix(&s[i..i + 16], 16).map_err(|_| ()))
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
 impl Modules {
+    /// Every bit of state, in order of module label and then conjunction input.
+    fn state_bits(&self) -> impl Iterator<Item = bool> + '_ {
+        self.0
+            .iter()
+            .sorted_by(|(a, _), (b, _)| a.cmp(b))
+            .flat_map(|(_, module)| match &module.type_ {
+                ModuleType::FlipFlop(state) => vec![*state],
+                ModuleType::Conjunction(memory) => memory
+                    .iter()
+                    .sorted_by(|(a, _), (b, _)| a.cmp(b))
+                    .map(|(_, value)| *value)
+                    .collect(),
+                ModuleType::Broadcast => vec![],
+            })
+    }
+
+    /// Mutable references to the bits of `state_bits`, in the same order.
+    fn state_bits_mut(&mut self) -> Vec<&mut bool> {
+        self.0
+            .iter_mut()
+            .sorted_by(|(a, _), (b, _)| a.cmp(b))
+            .flat_map(|(_, module)| match &mut module.type_ {
+                ModuleType::FlipFlop(state) => vec![state],
+                ModuleType::Conjunction(memory) => memory
+                    .iter_mut()
+                    .sorted_by(|(a, _), (b, _)| a.cmp(b))
+                    .map(|(_, value)| value)
+                    .collect(),
+                ModuleType::Broadcast => vec![],
+            })
+            .collect()
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let mut words = vec![];
+        for (i, bit) in self.state_bits().enumerate() {
+            if i % 64 == 0 {
+                words.push(0);
+            }
+            *words.last_mut().unwrap() |= (bit as u64) << (i % 64);
+        }
+        Snapshot(words)
+    }
+
+    fn restore(&mut self, snapshot: &Snapshot) -> Result<(), String> {
+        let bits = self.state_bits_mut();
+        if bits.len().div_ceil(64) != snapshot.0.len() {
+            return Err(format!(
+                "Snapshot has {} words but the circuit has {} bits of state",
+                snapshot.0.len(),
+                bits.len()
+            ));
+        }
+        for (i, bit) in bits.into_iter().enumerate() {
+            *bit = snapshot.0[i / 64] >> (i % 64) & 1 == 1;
+        }
+        Ok(())
+    }
+
     fn initialize_conjunctions(&mut self) {
         let mut inputs: HashMap<String, Vec<String>> = HashMap::new();
         for (label, Module { destinations, .. }) in self.0.iter() {
@@ -98,15 +318,234 @@ impl Modules {
         }
         for (label, Module { type_, .. }) in self.0.iter_mut() {
             if let ModuleType::Conjunction(conjunction) = type_ {
-                for input in inputs.get(label).unwrap() {
+                for input in inputs.get(label).into_iter().flatten() {
                     conjunction.insert(input.to_owned(), false);
                 }
             }
         }
     }
 
-    fn process_queue(&mut self, queue: &mut PulseQueue) {
+    /// Renders the network in Graphviz DOT, with the broadcaster, flip-flops
+    /// and conjunctions drawn as different shapes.
+    fn to_dot(&self) -> String {
+        let mut dot = "digraph modules {\n".to_owned();
+        for (label, module) in self.0.iter().sorted_by(|(a, _), (b, _)| a.cmp(b)) {
+            let shape = match module.type_ {
+                ModuleType::FlipFlop(_) => "box",
+                ModuleType::Conjunction(_) => "invtrapezium",
+                ModuleType::Broadcast => "doubleoctagon",
+            };
+            dot.push_str(&format!("    {label} [shape={shape}];\n"));
+            for destination in &module.destinations {
+                dot.push_str(&format!("    {label} -> {destination};\n"));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Splits the network into the independent binary counters started by
+    /// the broadcaster: each is a chain of flip-flops, some of which feed a
+    /// conjunction that resets the chain once they are all on. Every counter
+    /// must lead to the same final conjunction.
+    fn counters(&self) -> Result<Vec<Counter>, String> {
+        let broadcaster = self.0.get("broadcaster").ok_or("No broadcaster")?;
+        // The counter that each flip-flop seen so far belongs to.
+        let mut owners: HashMap<&String, &String> = HashMap::new();
+        let counters: Vec<Counter> = broadcaster
+            .destinations
+            .iter()
+            .map(|start| {
+                let mut bits = vec![];
+                let mut conjunction = None;
+                let mut label = Some(start);
+                while let Some(current) = label {
+                    let module = self.0.get(current).ok_or_else(|| {
+                        format!("Counter starting at {start} reaches non-module {current}")
+                    })?;
+                    let ModuleType::FlipFlop(_) = module.type_ else {
+                        return Err(format!(
+                            "Counter starting at {start} reaches non-flip-flop {current}"
+                        ));
+                    };
+                    match owners.insert(current, start) {
+                        Some(owner) if owner == start => {
+                            return Err(format!(
+                                "Counter starting at {start} loops back to {current}"
+                            ));
+                        }
+                        Some(owner) => {
+                            return Err(format!(
+                                "Flip-flop {current} is shared by the counters starting at \
+                                 {owner} and {start}"
+                            ));
+                        }
+                        None => {}
+                    }
+                    let (flip_flops, others): (Vec<_>, Vec<_>) =
+                        module.destinations.iter().partition(|destination| {
+                            matches!(
+                                self.0.get(*destination),
+                                Some(Module {
+                                    type_: ModuleType::FlipFlop(_),
+                                    ..
+                                })
+                            )
+                        });
+                    if flip_flops.len() > 1 || others.len() > 1 {
+                        return Err(format!(
+                            "Flip-flop {current} branches into {:?}",
+                            module.destinations
+                        ));
+                    }
+                    let bit = if let Some(other) = others.first() {
+                        if conjunction.is_some_and(|conjunction| conjunction != *other) {
+                            return Err(format!(
+                                "Counter starting at {start} feeds several conjunctions"
+                            ));
+                        }
+                        conjunction = Some(*other);
+                        true
+                    } else {
+                        false
+                    };
+                    bits.push(bit);
+                    label = flip_flops.first().copied();
+                }
+                let conjunction = conjunction
+                    .ok_or_else(|| format!("Counter starting at {start} feeds no conjunction"))?
+                    .clone();
+                let Some(Module {
+                    type_: ModuleType::Conjunction(_),
+                    destinations,
+                }) = self.0.get(&conjunction)
+                else {
+                    return Err(format!(
+                        "Counter starting at {start} feeds {conjunction}, not a conjunction"
+                    ));
+                };
+                let outputs = destinations
+                    .iter()
+                    .filter(|destination| {
+                        !matches!(
+                            self.0.get(*destination),
+                            Some(Module {
+                                type_: ModuleType::FlipFlop(_),
+                                ..
+                            })
+                        )
+                    })
+                    .cloned()
+                    .collect();
+                let final_conjunction = self.final_conjunction(&conjunction).ok_or_else(|| {
+                    format!("Counter starting at {start} leads to no final conjunction")
+                })?;
+                Ok(Counter {
+                    start: start.clone(),
+                    conjunction,
+                    bits,
+                    outputs,
+                    final_conjunction,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        if !counters
+            .iter()
+            .map(|counter| &counter.final_conjunction)
+            .all_equal()
+        {
+            return Err("Counters lead to different final conjunctions".to_owned());
+        }
+        Ok(counters)
+    }
+
+    /// The first conjunction downstream of `conjunction`, not passing through
+    /// flip-flops, that sends pulses out of the network.
+    fn final_conjunction(&self, conjunction: &str) -> Option<String> {
+        let mut seen = HashSet::from([conjunction]);
+        let mut queue = VecDeque::from([conjunction]);
+        while let Some(label) = queue.pop_front() {
+            let module = self.0.get(label)?;
+            for destination in &module.destinations {
+                match self.0.get(destination) {
+                    None if label != conjunction
+                        && matches!(module.type_, ModuleType::Conjunction(_)) =>
+                    {
+                        return Some(label.to_owned());
+                    }
+                    None
+                    | Some(Module {
+                        type_: ModuleType::FlipFlop(_),
+                        ..
+                    }) => {}
+                    Some(_) => {
+                        if seen.insert(destination) {
+                            queue.push_back(destination);
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn wiring_issues(&self) -> Vec<WiringIssue> {
+        let sinks = self
+            .0
+            .values()
+            .flat_map(|module| &module.destinations)
+            .filter(|destination| !self.0.contains_key(*destination))
+            .unique()
+            .sorted()
+            .map(|label| WiringIssue::Sink(label.clone()));
+        let inputless = self
+            .0
+            .iter()
+            .filter(|(_, module)| {
+                matches!(&module.type_, ModuleType::Conjunction(inputs) if inputs.is_empty())
+            })
+            .map(|(label, _)| label)
+            .sorted()
+            .map(|label| WiringIssue::InputlessConjunction(label.clone()));
+        let mut reachable = HashSet::from(["broadcaster"]);
+        let mut stack = vec!["broadcaster"];
+        while let Some(label) = stack.pop() {
+            for destination in self.0.get(label).into_iter().flat_map(|m| &m.destinations) {
+                if reachable.insert(destination) {
+                    stack.push(destination);
+                }
+            }
+        }
+        let unreachable = self
+            .0
+            .keys()
+            .filter(|label| !reachable.contains(label.as_str()))
+            .sorted()
+            .map(|label| WiringIssue::UnreachableFromBroadcaster(label.clone()));
+        sinks.chain(inputless).chain(unreachable).collect()
+    }
+
+    /// Prints every wiring issue, failing if any of them is an error.
+    fn validate(&self, strict: bool) -> Result<(), String> {
+        let mut errors = 0;
+        for issue in self.wiring_issues() {
+            if issue.is_error(strict) {
+                errors += 1;
+                eprintln!("error: {issue}");
+            } else {
+                eprintln!("warning: {issue}");
+            }
+        }
+        if errors > 0 {
+            Err(format!("{errors} wiring errors"))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn process_queue(&mut self, queue: &mut PulseQueue, trace: &Trace) {
         while let Some(pulse) = queue.dequeue() {
+            trace.log(&pulse, queue.button_presses);
             if let Some(module) = self.0.get_mut(&pulse.destination) {
                 if let Some(value) = module.type_.process(&pulse) {
                     for destination in module.destinations.iter() {
@@ -126,10 +565,12 @@ struct Input(Modules);
 
 impl Input {
     fn parse() -> Self {
+        Self::from_lines(stdin().lines().map(|line| line.unwrap()))
+    }
+
+    fn from_lines(lines: impl Iterator<Item = String>) -> Self {
         let mut modules = Modules(
-            stdin()
-                .lines()
-                .map(|line| line.unwrap())
+            lines
                 .map(|line| {
                     let (type_label, destinations) = line.split_once(" -> ").unwrap();
                     let (type_, label) = if let Some(label) = type_label.strip_prefix('%') {
@@ -157,16 +598,219 @@ impl Input {
         Self(modules)
     }
 
-    fn solve(&mut self) -> usize {
+    /// Presses the button until the circuit returns to its starting state,
+    /// then skips ahead by whole cycles.
+    fn solve(&mut self, trace: &Trace) -> usize {
+        let start = self.0.snapshot();
         let mut queue = PulseQueue::new();
-        for _ in 0..1000 {
+        let mut sent = vec![(0, 0)];
+        while queue.button_presses < PRESSES {
             queue.enqueue(Pulse::button());
-            self.0.process_queue(&mut queue);
+            self.0.process_queue(&mut queue, trace);
+            sent.push((queue.lows_sent, queue.highs_sent));
+            if self.0.snapshot() == start {
+                break;
+            }
         }
-        queue.summary()
+        let period = queue.button_presses;
+        let (cycles, remainder) = (PRESSES / period, PRESSES % period);
+        let lows = cycles * sent[period].0 + sent[remainder].0;
+        let highs = cycles * sent[period].1 + sent[remainder].1;
+        lows * highs
     }
 }
 
+const PRESSES: usize = 1000;
+
+#[cfg(test)]
+fn input(lines: &[&str]) -> Input {
+    Input::from_lines(lines.iter().map(|line| (*line).to_owned()))
+}
+
+#[cfg(test)]
+const EXAMPLES: [&[&str]; 2] = [
+    &[
+        "broadcaster -> a, b, c",
+        "%a -> b",
+        "%b -> c",
+        "%c -> inv",
+        "&inv -> a",
+    ],
+    &[
+        "broadcaster -> a",
+        "%a -> inv, con",
+        "&inv -> b",
+        "%b -> con",
+        "&con -> output",
+    ],
+];
+
+#[test]
+fn test_solve() {
+    assert_eq!(input(EXAMPLES[0]).solve(&Trace::default()), 32000000);
+    assert_eq!(input(EXAMPLES[1]).solve(&Trace::default()), 11687500);
+}
+
+#[test]
+fn test_counters() {
+    let counters = input(&[
+        "broadcaster -> a1, b1",
+        "%a1 -> a2, ca",
+        "%a2 -> ca",
+        "&ca -> ia, a1",
+        "&ia -> fd",
+        "%b1 -> b2, cb",
+        "%b2 -> b3, cb",
+        "%b3 -> cb",
+        "&cb -> ib, b1",
+        "&ib -> fd",
+        "&fd -> rx",
+    ])
+    .0
+    .counters()
+    .unwrap();
+    assert_eq!(
+        counters.iter().map(Counter::to_string).collect_vec(),
+        [
+            "a1 -> ca -> ia -> ... -> fd: bits 11, period 3",
+            "b1 -> cb -> ib -> ... -> fd: bits 111, period 7",
+        ]
+    );
+    assert!(input(EXAMPLES[0]).0.counters().is_err());
+    let shared = input(&[
+        "broadcaster -> a, b",
+        "%a -> b",
+        "%b -> c",
+        "&c -> d",
+        "&d -> rx",
+    ]);
+    assert_eq!(
+        shared.0.counters().err(),
+        Some("Flip-flop b is shared by the counters starting at a and b".to_owned())
+    );
+    assert!(input(EXAMPLES[1]).0.counters().is_err());
+    for wiring in [
+        &["broadcaster -> a", "%a -> c", "&c -> a"][..],
+        &[
+            "broadcaster -> a, b",
+            "%a -> c",
+            "&c -> d",
+            "&d -> rx",
+            "%b -> e",
+            "&e -> f",
+            "&f -> output",
+        ],
+        &["broadcaster -> a", "%a -> a"],
+        &["broadcaster -> a", "%a -> b", "%b -> a"],
+        &["broadcaster -> output"],
+        &["broadcaster -> a", "%a -> output"],
+    ] {
+        assert!(input(wiring).0.counters().is_err());
+    }
+}
+
+#[test]
+fn test_snapshot() {
+    let mut input = input(EXAMPLES[1]);
+    let mut queue = PulseQueue::new();
+    queue.enqueue(Pulse::button());
+    input.0.process_queue(&mut queue, &Trace::default());
+    let snapshot = input.0.snapshot();
+    assert_eq!(snapshot.to_string().parse(), Ok(snapshot.clone()));
+    input.0.restore(&Snapshot(vec![0])).unwrap();
+    assert_ne!(input.0.snapshot(), snapshot);
+    input.0.restore(&snapshot).unwrap();
+    assert_eq!(input.0.snapshot(), snapshot);
+    assert!(input.0.restore(&Snapshot(vec![0, 0])).is_err());
+    assert_eq!("é".repeat(8).parse::<Snapshot>(), Err(()));
+}
+
+struct Options {
+    trace: Trace,
+    start: Option<Snapshot>,
+    print_state: bool,
+    dot: bool,
+    counters: bool,
+    strict: bool,
+}
+
+impl Options {
+    /// Usage: `20_1 [--from-state KEY] [--print-state] [--dot] [--counters]
+    /// [--strict] [TRACE OPTIONS]`
+    fn parse() -> Self {
+        let mut options = Self {
+            trace: Trace::default(),
+            start: None,
+            print_state: false,
+            dot: false,
+            counters: false,
+            strict: false,
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--from-state" => {
+                    let key = args.next().expect("--from-state needs a value");
+                    options.start = Some(key.parse().expect("Invalid state key"));
+                }
+                "--print-state" => options.print_state = true,
+                "--dot" => options.dot = true,
+                "--counters" => options.counters = true,
+                "--strict" => options.strict = true,
+                _ => {
+                    if !options.trace.parse_arg(&arg, &mut args) {
+                        panic!("Unknown argument {arg}");
+                    }
+                }
+            }
+        }
+        options
+    }
+}
+
+#[test]
+fn test_wiring_issues() {
+    let modules = input(&["broadcaster -> a", "%a -> output", "&c -> a", "%u -> a"]).0;
+    assert_eq!(
+        modules
+            .wiring_issues()
+            .iter()
+            .map(WiringIssue::to_string)
+            .collect_vec(),
+        [
+            "output receives pulses but is not a module",
+            "conjunction c has no inputs",
+            "c is unreachable from broadcaster",
+            "u is unreachable from broadcaster",
+        ]
+    );
+    assert_eq!(modules.validate(false), Ok(()));
+    assert_eq!(modules.validate(true), Err("3 wiring errors".to_owned()));
+}
+
 fn main() {
-    println!("{}", Input::parse().solve())
+    let options = Options::parse();
+    let mut input = Input::parse();
+    if let Err(error) = input.0.validate(options.strict) {
+        panic!("{error}");
+    }
+    if options.dot {
+        print!("{}", input.0.to_dot());
+        return;
+    }
+    if options.counters {
+        match input.0.counters() {
+            Ok(counters) => counters.iter().for_each(|counter| eprintln!("{counter}")),
+            Err(error) => eprintln!("Not a network of counters: {error}"),
+        }
+    }
+    if let Some(start) = &options.start {
+        if let Err(error) = input.0.restore(start) {
+            panic!("{error}");
+        }
+    }
+    println!("{}", input.solve(&options.trace));
+    if options.print_state {
+        eprintln!("{}", input.0.snapshot());
+    }
 }
@@ -1,8 +1,13 @@
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::{self, Display, Formatter},
     io::stdin,
+    ops::RangeInclusive,
 };
 
+use itertools::Itertools;
+use num::integer::lcm;
+
 struct Pulse {
     source: String,
     destination: String,
@@ -22,15 +27,18 @@ impl Pulse {
 struct PulseQueue {
     queue: VecDeque<Pulse>,
     button_presses: usize,
-    rx_was_sent_low_pulse: bool,
+    /// The module whose incoming high pulses are recorded in `high_presses`.
+    watched: String,
+    high_presses: HashMap<String, Vec<usize>>,
 }
 
 impl PulseQueue {
-    fn new() -> Self {
+    fn new(watched: String) -> Self {
         Self {
             queue: VecDeque::new(),
             button_presses: 0,
-            rx_was_sent_low_pulse: false,
+            watched,
+            high_presses: HashMap::new(),
         }
     }
 
@@ -38,8 +46,11 @@ impl PulseQueue {
         if pulse.source == "button" {
             self.button_presses += 1;
         }
-        if pulse.destination == "rx" && !pulse.value {
-            self.rx_was_sent_low_pulse = true;
+        if pulse.destination == self.watched && pulse.value {
+            let presses = self.high_presses.entry(pulse.source.clone()).or_default();
+            if presses.last() != Some(&self.button_presses) {
+                presses.push(self.button_presses);
+            }
         }
         self.queue.push_back(pulse);
     }
@@ -49,6 +60,62 @@ impl PulseQueue {
     }
 }
 
+/// Prints processed pulses in the puzzle's `a -high-> b` notation, prefixed by
+/// the button press they belong to. Empty filters match everything.
+#[derive(Default)]
+struct Trace {
+    enabled: bool,
+    modules: Vec<String>,
+    value: Option<bool>,
+    presses: Option<RangeInclusive<usize>>,
+}
+
+impl Trace {
+    /// Applies `arg` if it is a trace option, taking its value from `args`.
+    fn parse_arg(&mut self, arg: &str, args: &mut impl Iterator<Item = String>) -> bool {
+        let mut value = || args.next().unwrap_or_else(|| panic!("{arg} needs a value"));
+        match arg {
+            "--trace" => {}
+            "--trace-module" => self.modules.push(value()),
+            "--trace-value" => {
+                self.value = Some(match value().as_str() {
+                    "high" => true,
+                    "low" => false,
+                    other => panic!("Invalid pulse value {other}"),
+                })
+            }
+            "--trace-presses" => {
+                let range = value();
+                let (first, last) = range.split_once('-').expect("Expected FIRST-LAST");
+                self.presses = Some(first.parse().unwrap()..=last.parse().unwrap());
+            }
+            _ => return false,
+        }
+        self.enabled = true;
+        true
+    }
+
+    fn log(&self, pulse: &Pulse, button_presses: usize) {
+        if self.enabled
+            && (self.modules.is_empty()
+                || self.modules.contains(&pulse.source)
+                || self.modules.contains(&pulse.destination))
+            && self.value.is_none_or(|value| value == pulse.value)
+            && self
+                .presses
+                .as_ref()
+                .is_none_or(|presses| presses.contains(&button_presses))
+        {
+            eprintln!(
+                "{button_presses}: {} -{}-> {}",
+                pulse.source,
+                if pulse.value { "high" } else { "low" },
+                pulse.destination
+            );
+        }
+    }
+}
+
 enum ModuleType {
     FlipFlop(bool),
     Conjunction(HashMap<String, bool>),
@@ -80,6 +147,36 @@ struct Module {
     destinations: Vec<String>,
 }
 
+/// A suspicious piece of wiring found by `Modules::wiring_issues`.
+enum WiringIssue {
+    /// A destination that is not itself a module, such as `rx` or `output`.
+    Sink(String),
+    InputlessConjunction(String),
+    UnreachableFromBroadcaster(String),
+}
+
+impl WiringIssue {
+    /// Sinks are expected in most circuits, so only the other issues become
+    /// errors in strict mode.
+    fn is_error(&self, strict: bool) -> bool {
+        strict && !matches!(self, WiringIssue::Sink(_))
+    }
+}
+
+impl Display for WiringIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            WiringIssue::Sink(label) => write!(f, "{label} receives pulses but is not a module"),
+            WiringIssue::InputlessConjunction(label) => {
+                write!(f, "conjunction {label} has no inputs")
+            }
+            WiringIssue::UnreachableFromBroadcaster(label) => {
+                write!(f, "{label} is unreachable from broadcaster")
+            }
+        }
+    }
+}
+
 struct Modules(HashMap<String, Module>);
 
 impl Modules {
@@ -95,15 +192,92 @@ impl Modules {
         }
         for (label, Module { type_, .. }) in self.0.iter_mut() {
             if let ModuleType::Conjunction(conjunction) = type_ {
-                for input in inputs.get(label).unwrap() {
+                for input in inputs.get(label).into_iter().flatten() {
                     conjunction.insert(input.to_owned(), false);
                 }
             }
         }
     }
 
-    fn process_queue(&mut self, queue: &mut PulseQueue) {
+    /// The conjunction that is the only module sending pulses to `label`,
+    /// along with that conjunction's inputs.
+    fn feeder_of(&self, label: &str) -> Result<(String, Vec<String>), String> {
+        let feeders: Vec<_> = self
+            .0
+            .iter()
+            .filter(|(_, module)| module.destinations.iter().any(|d| d == label))
+            .collect();
+        let [(feeder, module)] = feeders[..] else {
+            return Err(format!(
+                "Expected exactly one module feeding {label}, found {}",
+                feeders.len()
+            ));
+        };
+        let ModuleType::Conjunction(inputs) = &module.type_ else {
+            return Err(format!(
+                "Module {feeder} feeding {label} is not a conjunction"
+            ));
+        };
+        Ok((feeder.clone(), inputs.keys().cloned().collect()))
+    }
+
+    fn wiring_issues(&self) -> Vec<WiringIssue> {
+        let sinks = self
+            .0
+            .values()
+            .flat_map(|module| &module.destinations)
+            .filter(|destination| !self.0.contains_key(*destination))
+            .unique()
+            .sorted()
+            .map(|label| WiringIssue::Sink(label.clone()));
+        let inputless = self
+            .0
+            .iter()
+            .filter(|(_, module)| {
+                matches!(&module.type_, ModuleType::Conjunction(inputs) if inputs.is_empty())
+            })
+            .map(|(label, _)| label)
+            .sorted()
+            .map(|label| WiringIssue::InputlessConjunction(label.clone()));
+        let mut reachable = HashSet::from(["broadcaster"]);
+        let mut stack = vec!["broadcaster"];
+        while let Some(label) = stack.pop() {
+            for destination in self.0.get(label).into_iter().flat_map(|m| &m.destinations) {
+                if reachable.insert(destination) {
+                    stack.push(destination);
+                }
+            }
+        }
+        let unreachable = self
+            .0
+            .keys()
+            .filter(|label| !reachable.contains(label.as_str()))
+            .sorted()
+            .map(|label| WiringIssue::UnreachableFromBroadcaster(label.clone()));
+        sinks.chain(inputless).chain(unreachable).collect()
+    }
+
+    /// Prints every wiring issue, failing if any of them is an error.
+    fn validate(&self, strict: bool) -> Result<(), String> {
+        let mut errors = 0;
+        for issue in self.wiring_issues() {
+            if issue.is_error(strict) {
+                errors += 1;
+                eprintln!("error: {issue}");
+            } else {
+                eprintln!("warning: {issue}");
+            }
+        }
+        if errors > 0 {
+            Err(format!("{errors} wiring errors"))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn process_queue(&mut self, queue: &mut PulseQueue, trace: &Trace) {
         while let Some(pulse) = queue.dequeue() {
+            trace.log(&pulse, queue.button_presses);
             if let Some(module) = self.0.get_mut(&pulse.destination) {
                 if let Some(value) = module.type_.process(&pulse) {
                     for destination in module.destinations.iter() {
@@ -123,10 +297,12 @@ struct Input(Modules);
 
 impl Input {
     fn parse() -> Self {
+        Self::from_lines(stdin().lines().map(|line| line.unwrap()))
+    }
+
+    fn from_lines(lines: impl Iterator<Item = String>) -> Self {
         let mut modules = Modules(
-            stdin()
-                .lines()
-                .map(|line| line.unwrap())
+            lines
                 .map(|line| {
                     let (type_label, destinations) = line.split_once(" -> ").unwrap();
                     let (type_, label) = if let Some(label) = type_label.strip_prefix('%') {
@@ -154,16 +330,136 @@ impl Input {
         Self(modules)
     }
 
-    fn solve(&mut self) -> usize {
-        let mut queue = PulseQueue::new();
-        while !queue.rx_was_sent_low_pulse {
+    /// Assumes `rx` is fed by a single conjunction whose inputs each send it
+    /// a high pulse once every `n` presses; `rx` then receives a low pulse
+    /// after the LCM of those periods.
+    fn solve(&mut self, trace: &Trace) -> Result<usize, String> {
+        let (feeder, inputs) = self.0.feeder_of("rx")?;
+        if inputs.is_empty() {
+            return Err(format!("Conjunction {feeder} feeding rx has no inputs"));
+        }
+        let mut queue = PulseQueue::new(feeder.clone());
+        while inputs
+            .iter()
+            .any(|input| queue.high_presses.get(input).map_or(0, Vec::len) < 2)
+        {
+            if queue.button_presses >= MAX_PRESSES {
+                return Err(format!(
+                    "Not every input of {feeder} sent it two high pulses within {MAX_PRESSES} presses"
+                ));
+            }
             queue.enqueue(Pulse::button());
-            self.0.process_queue(&mut queue);
+            self.0.process_queue(&mut queue, trace);
         }
-        queue.button_presses
+        inputs.iter().try_fold(1, |acc, input| {
+            let presses = &queue.high_presses[input];
+            let (first, second) = (presses[0], presses[1]);
+            if second - first != first {
+                return Err(format!(
+                    "Input {input} of {feeder} sent high pulses at presses {first} and {second}, \
+                     which is not a cycle starting from the first press"
+                ));
+            }
+            Ok(lcm(acc, first))
+        })
     }
 }
 
+const MAX_PRESSES: usize = 1_000_000;
+
+struct Options {
+    trace: Trace,
+    strict: bool,
+}
+
+impl Options {
+    /// Usage: `20_2 [--strict] [TRACE OPTIONS]`
+    fn parse() -> Self {
+        let mut options = Self {
+            trace: Trace::default(),
+            strict: false,
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--strict" => options.strict = true,
+                _ => {
+                    if !options.trace.parse_arg(&arg, &mut args) {
+                        panic!("Unknown argument {arg}");
+                    }
+                }
+            }
+        }
+        options
+    }
+}
+
+#[cfg(test)]
+fn input(lines: &[&str]) -> Input {
+    Input::from_lines(lines.iter().map(|line| (*line).to_owned()))
+}
+
+#[test]
+fn test_solve() {
+    let mut counters = input(&[
+        "broadcaster -> a1, b1",
+        "%a1 -> a2, ca",
+        "%a2 -> ca",
+        "&ca -> ia, a1",
+        "&ia -> fd",
+        "%b1 -> b2, cb",
+        "%b2 -> b3, cb",
+        "%b3 -> cb",
+        "&cb -> ib, b1",
+        "&ib -> fd",
+        "&fd -> rx",
+    ]);
+    assert_eq!(counters.solve(&Trace::default()), Ok(21));
+    let mut flip_flop_feeder = input(&["broadcaster -> a", "%a -> rx"]);
+    assert_eq!(
+        flip_flop_feeder.solve(&Trace::default()),
+        Err("Module a feeding rx is not a conjunction".to_owned())
+    );
+    let mut no_feeder = input(&["broadcaster -> a", "%a -> output"]);
+    assert_eq!(
+        no_feeder.solve(&Trace::default()),
+        Err("Expected exactly one module feeding rx, found 0".to_owned())
+    );
+    let mut inputless_feeder = input(&["broadcaster -> a", "%a -> b", "&fd -> rx", "&b -> a"]);
+    assert_eq!(
+        inputless_feeder.solve(&Trace::default()),
+        Err("Conjunction fd feeding rx has no inputs".to_owned())
+    );
+}
+
+#[test]
+fn test_wiring_issues() {
+    let modules = input(&["broadcaster -> a", "%a -> output", "&c -> a", "%u -> a"]).0;
+    assert_eq!(
+        modules
+            .wiring_issues()
+            .iter()
+            .map(WiringIssue::to_string)
+            .collect_vec(),
+        [
+            "output receives pulses but is not a module",
+            "conjunction c has no inputs",
+            "c is unreachable from broadcaster",
+            "u is unreachable from broadcaster",
+        ]
+    );
+    assert_eq!(modules.validate(false), Ok(()));
+    assert_eq!(modules.validate(true), Err("3 wiring errors".to_owned()));
+}
+
 fn main() {
-    println!("{}", Input::parse().solve())
+    let options = Options::parse();
+    let mut input = Input::parse();
+    if let Err(error) = input.0.validate(options.strict) {
+        panic!("{error}");
+    }
+    match input.solve(&options.trace) {
+        Ok(answer) => println!("{answer}"),
+        Err(error) => panic!("{error}"),
+    }
 }
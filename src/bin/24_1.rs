@@ -1,8 +1,10 @@
 use std::{io::stdin, str::FromStr};
 
 use itertools::Itertools;
+use num::{Signed, Zero};
 use num_rational::BigRational;
 
+#[derive(PartialEq)]
 struct Dimensions {
     x: BigRational,
     y: BigRational,
@@ -22,11 +24,30 @@ impl FromStr for Dimensions {
 }
 
 impl Dimensions {
-    fn is_in_test_area(&self) -> bool {
-        BigRational::from_integer(200000000000000i64.into()) <= self.x
-            && self.x <= BigRational::from_integer(400000000000000i64.into())
-            && BigRational::from_integer(200000000000000i64.into()) <= self.y
-            && self.y <= BigRational::from_integer(400000000000000i64.into())
+    fn cross(&self, other: &Self) -> BigRational {
+        &self.x * &other.y - &self.y * &other.x
+    }
+
+    fn dot(&self, other: &Self) -> BigRational {
+        &self.x * &other.x + &self.y * &other.y
+    }
+}
+
+struct TestArea {
+    min: BigRational,
+    max: BigRational,
+}
+
+impl TestArea {
+    fn new(min: i64, max: i64) -> Self {
+        Self {
+            min: BigRational::from_integer(min.into()),
+            max: BigRational::from_integer(max.into()),
+        }
+    }
+
+    fn contains(&self, point: &Dimensions) -> bool {
+        self.min <= point.x && point.x <= self.max && self.min <= point.y && point.y <= self.max
     }
 }
 
@@ -48,21 +69,131 @@ impl FromStr for Hailstone {
 }
 
 impl Hailstone {
-    fn intersection(a: &Hailstone, b: &Hailstone) -> Option<Dimensions> {
-        let numerator = &a.p.y - &b.p.y + (&b.v.y * &b.p.x / &b.v.x) - (&a.v.y * &a.p.x / &a.v.x);
-        let denominator = &b.v.y / &b.v.x - &a.v.y / &a.v.x;
-        if denominator == BigRational::from_integer(0.into()) {
-            None
+    fn position_at(&self, t: &BigRational) -> Dimensions {
+        Dimensions {
+            x: &self.p.x + &self.v.x * t,
+            y: &self.p.y + &self.v.y * t,
+        }
+    }
+
+    /// Solves `a.p + a.v * t = b.p + b.v * s` for the times `(t, s)` at which
+    /// each hailstone reaches the crossing point.
+    fn intersection(a: &Hailstone, b: &Hailstone) -> Intersection {
+        let d = Dimensions {
+            x: &b.p.x - &a.p.x,
+            y: &b.p.y - &a.p.y,
+        };
+        let determinant = a.v.cross(&b.v);
+        if determinant.is_zero() {
+            if d.cross(&a.v).is_zero() && d.cross(&b.v).is_zero() {
+                Intersection::Collinear {
+                    overlapping: !d.dot(&a.v).is_negative() || b.v.dot(&a.v).is_positive(),
+                }
+            } else {
+                Intersection::Parallel
+            }
         } else {
-            let x = numerator / denominator;
-            let y = &a.p.y + &a.v.y * ((&x - &a.p.x) / &a.v.x);
-            Some(Dimensions { x, y })
+            let t = d.cross(&b.v) / &determinant;
+            let s = d.cross(&a.v) / &determinant;
+            Intersection::Point { t, s }
+        }
+    }
+
+    fn crossing(a: &Hailstone, b: &Hailstone, test_area: &TestArea) -> Crossing {
+        match Hailstone::intersection(a, b) {
+            Intersection::Parallel => Crossing::Never,
+            Intersection::Collinear { overlapping } => Crossing::Collinear { overlapping },
+            Intersection::Point { t, s } => match (t.is_negative(), s.is_negative()) {
+                (false, false) => {
+                    let point = a.position_at(&t);
+                    if test_area.contains(&point) {
+                        Crossing::Inside(point)
+                    } else {
+                        Crossing::Outside(point)
+                    }
+                }
+                (a, b) => Crossing::Past { a, b },
+            },
+        }
+    }
+}
+
+enum Intersection {
+    Point { t: BigRational, s: BigRational },
+    Parallel,
+    Collinear { overlapping: bool },
+}
+
+enum Crossing {
+    Inside(Dimensions),
+    Outside(Dimensions),
+    /// Which of the two hailstones would have had to travel back in time.
+    Past {
+        a: bool,
+        b: bool,
+    },
+    Never,
+    /// Both paths lie on the same line; `overlapping` if their future paths
+    /// share any points.
+    Collinear {
+        overlapping: bool,
+    },
+}
+
+impl Crossing {
+    fn describe(&self, a: usize, b: usize) -> String {
+        match self {
+            Crossing::Inside(point) => format!(
+                "Hailstones {a} and {b} will cross inside the test area (at x={}, y={}).",
+                point.x, point.y
+            ),
+            Crossing::Outside(point) => format!(
+                "Hailstones {a} and {b} will cross outside the test area (at x={}, y={}).",
+                point.x, point.y
+            ),
+            Crossing::Past { a: true, b: true } => {
+                format!("Hailstones {a} and {b} crossed in the past for both hailstones.")
+            }
+            Crossing::Past { a: true, .. } => {
+                format!("Hailstones {a} and {b} crossed in the past for hailstone {a}.")
+            }
+            Crossing::Past { .. } => {
+                format!("Hailstones {a} and {b} crossed in the past for hailstone {b}.")
+            }
+            Crossing::Never => format!("Hailstones {a} and {b} never intersect."),
+            Crossing::Collinear { overlapping: true } => {
+                format!("Hailstones {a} and {b} travel along the same line and overlap.")
+            }
+            Crossing::Collinear { overlapping: false } => {
+                format!("Hailstones {a} and {b} travel along the same line but never meet.")
+            }
         }
     }
+}
 
-    fn is_future(&self, point: &Dimensions) -> bool {
-        (&point.x - &self.p.x) * &self.v.x > BigRational::from_integer(0.into())
-            && (&point.y - &self.p.y) * &self.v.y > BigRational::from_integer(0.into())
+struct Options {
+    test_area: TestArea,
+    report: bool,
+}
+
+impl Options {
+    /// Usage: `24_1 [--report] [MIN MAX]`
+    fn parse() -> Self {
+        let mut report = false;
+        let mut bounds = vec![];
+        for arg in std::env::args().skip(1) {
+            if arg == "--report" {
+                report = true;
+            } else {
+                bounds.push(arg.parse().expect("Test area bounds must be integers"));
+            }
+        }
+        let test_area = match bounds[..] {
+            [] => TestArea::new(200000000000000, 400000000000000),
+            [min, max] => TestArea::new(min, max),
+            _ => panic!("Expected exactly two test area bounds"),
+        };
+        Self { test_area, report }
     }
 }
 
@@ -78,19 +209,100 @@ impl Input {
         )
     }
 
-    fn solve(&self) -> usize {
+    fn crossings<'a>(
+        &'a self,
+        test_area: &'a TestArea,
+    ) -> impl Iterator<Item = (usize, usize, Crossing)> + 'a {
         self.0
             .iter()
+            .enumerate()
             .tuple_combinations()
-            .filter(|(a, b)| {
-                Hailstone::intersection(a, b).is_some_and(|point| {
-                    a.is_future(&point) && b.is_future(&point) && point.is_in_test_area()
-                })
-            })
+            .map(|((i, a), (j, b))| (i, j, Hailstone::crossing(a, b, test_area)))
+    }
+
+    fn report(&self, test_area: &TestArea) {
+        for (i, j, crossing) in self.crossings(test_area) {
+            eprintln!("{}", crossing.describe(i, j));
+        }
+    }
+
+    fn solve(&self, test_area: &TestArea) -> usize {
+        self.crossings(test_area)
+            .filter(|(_, _, crossing)| matches!(crossing, Crossing::Inside(_)))
             .count()
     }
 }
 
+#[test]
+fn test_example() {
+    let input = Input(
+        [
+            "19, 13, 30 @ -2, 1, -2",
+            "18, 19, 22 @ -1, -1, -2",
+            "20, 25, 34 @ -2, -2, -4",
+            "12, 31, 28 @ -1, -2, -1",
+            "20, 19, 15 @ 1, -5, -3",
+        ]
+        .iter()
+        .map(|line| line.parse().unwrap())
+        .collect(),
+    );
+    let test_area = TestArea::new(7, 27);
+    assert_eq!(input.solve(&test_area), 2);
+    let kinds = input
+        .crossings(&test_area)
+        .map(|(_, _, crossing)| match crossing {
+            Crossing::Inside(_) => "inside",
+            Crossing::Outside(_) => "outside",
+            Crossing::Past { .. } => "past",
+            Crossing::Never => "never",
+            Crossing::Collinear { .. } => "collinear",
+        })
+        .collect_vec();
+    assert_eq!(
+        kinds,
+        [
+            "inside", "inside", "outside", "past", "never", "outside", "past", "outside", "past",
+            "past"
+        ]
+    );
+}
+
+#[test]
+fn test_vertical_and_collinear() {
+    let parse = |line: &str| -> Hailstone { line.parse().unwrap() };
+    let test_area = TestArea::new(0, 10);
+    let crossing = Hailstone::crossing(
+        &parse("5, 0, 0 @ 0, 1, 0"),
+        &parse("0, 5, 0 @ 1, 0, 0"),
+        &test_area,
+    );
+    assert!(matches!(crossing, Crossing::Inside(point) if point == parse("5, 5, 0 @ 0, 0, 0").p));
+    let crossing = Hailstone::crossing(
+        &parse("0, 0, 0 @ 1, 1, 0"),
+        &parse("4, 4, 0 @ -1, -1, 0"),
+        &test_area,
+    );
+    assert!(matches!(
+        crossing,
+        Crossing::Collinear { overlapping: true }
+    ));
+    let crossing = Hailstone::crossing(
+        &parse("0, 0, 0 @ -1, -1, 0"),
+        &parse("4, 4, 0 @ 1, 1, 0"),
+        &test_area,
+    );
+    assert!(matches!(
+        crossing,
+        Crossing::Collinear { overlapping: false }
+    ));
+}
+
 fn main() {
-    println!("{}", Input::parse().solve())
+    let options = Options::parse();
+    let input = Input::parse();
+    if options.report {
+        input.report(&options.test_area);
+    }
+    println!("{}", input.solve(&options.test_area))
 }
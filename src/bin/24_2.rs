@@ -1,16 +1,14 @@
-use std::{
-    io::stdin,
-    ops::{Mul, SubAssign},
-    str::FromStr,
-};
+use std::{io::stdin, str::FromStr};
 
 use itertools::Itertools;
+use num::{BigInt, Signed, Zero};
+use num_rational::BigRational;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct Dimensions {
-    x: f64,
-    y: f64,
-    z: f64,
+    x: BigRational,
+    y: BigRational,
+    z: BigRational,
 }
 
 impl FromStr for Dimensions {
@@ -26,24 +24,30 @@ impl FromStr for Dimensions {
     }
 }
 
-impl SubAssign for Dimensions {
-    fn sub_assign(&mut self, rhs: Self) {
-        self.x -= rhs.x;
-        self.y -= rhs.y;
-        self.z -= rhs.z;
+impl Dimensions {
+    fn sub(&self, other: &Self) -> Self {
+        Self {
+            x: &self.x - &other.x,
+            y: &self.y - &other.y,
+            z: &self.z - &other.z,
+        }
     }
-}
-
-impl Mul<f64> for Dimensions {
-    type Output = Self;
 
-    fn mul(self, rhs: f64) -> Self::Output {
+    fn cross(&self, other: &Self) -> Self {
         Self {
-            x: self.x * rhs,
-            y: self.y * rhs,
-            z: self.z * rhs,
+            x: &self.y * &other.z - &self.z * &other.y,
+            y: &self.z * &other.x - &self.x * &other.z,
+            z: &self.x * &other.y - &self.y * &other.x,
         }
     }
+
+    fn components(&self) -> [&BigRational; 3] {
+        [&self.x, &self.y, &self.z]
+    }
+
+    fn is_integer(&self) -> bool {
+        self.components().iter().all(|c| c.is_integer())
+    }
 }
 
 struct Hailstone {
@@ -63,130 +67,124 @@ impl FromStr for Hailstone {
     }
 }
 
+impl Hailstone {
+    /// The rock hits hailstone `i` iff `(P - p_i) × (V - v_i) = 0`. The `P × V`
+    /// term is shared by every hailstone, so subtracting the equations for `i`
+    /// and `j` leaves three equations linear in `P` and `V`.
+    fn pair_equations(a: &Hailstone, b: &Hailstone) -> [[BigRational; 7]; 3] {
+        let d = b.v.sub(&a.v);
+        let e = b.p.sub(&a.p);
+        let r = b.p.cross(&b.v).sub(&a.p.cross(&a.v));
+        let zero = BigRational::zero;
+        [
+            [
+                zero(),
+                d.z.clone(),
+                -d.y.clone(),
+                zero(),
+                -e.z.clone(),
+                e.y.clone(),
+                r.x,
+            ],
+            [
+                -d.z.clone(),
+                zero(),
+                d.x.clone(),
+                e.z.clone(),
+                zero(),
+                -e.x.clone(),
+                r.y,
+            ],
+            [d.y, -d.x, zero(), -e.y, e.x, zero(), r.z],
+        ]
+    }
+}
+
+/// Solves a square linear system given as augmented rows, returning `None` if
+/// it is singular.
+fn gaussian_elimination<const N: usize, const M: usize>(
+    mut rows: [[BigRational; M]; N],
+) -> Option<[BigRational; N]> {
+    for column in 0..N {
+        let pivot = (column..N).find(|&row| !rows[row][column].is_zero())?;
+        rows.swap(column, pivot);
+        let pivot_row = rows[column].clone();
+        for (row_i, row) in rows.iter_mut().enumerate() {
+            if row_i != column && !row[column].is_zero() {
+                let factor = &row[column] / &pivot_row[column];
+                for (cell, pivot_cell) in row.iter_mut().zip(&pivot_row) {
+                    *cell -= &factor * pivot_cell;
+                }
+            }
+        }
+    }
+    Some(std::array::from_fn(|i| &rows[i][N] / &rows[i][i]))
+}
+
 #[derive(Debug)]
-struct Solution {
+struct Rock {
     p: Dimensions,
     v: Dimensions,
-    t: Vec<f64>,
 }
 
-impl Solution {
-    fn new(hailstones: &[Hailstone]) -> Self {
-        Self {
+impl Rock {
+    fn from_triple(a: &Hailstone, b: &Hailstone, c: &Hailstone) -> Option<Self> {
+        let [r0, r1, r2] = Hailstone::pair_equations(a, b);
+        let [r3, r4, r5] = Hailstone::pair_equations(a, c);
+        let [px, py, pz, vx, vy, vz] = gaussian_elimination([r0, r1, r2, r3, r4, r5])?;
+        Some(Self {
             p: Dimensions {
-                x: 0.,
-                y: 0.,
-                z: 0.,
+                x: px,
+                y: py,
+                z: pz,
             },
             v: Dimensions {
-                x: 0.,
-                y: 0.,
-                z: 0.,
+                x: vx,
+                y: vy,
+                z: vz,
             },
-            t: hailstones.iter().map(|_| 0.).collect(),
-        }
-    }
-
-    fn error(&self, hailstones: &[Hailstone]) -> f64 {
-        self.t
-            .iter()
-            .zip(hailstones)
-            .map(|(t, hailstone)| {
-                ((self.v.x * t + self.p.x) - (hailstone.v.x * t + hailstone.p.x)).powi(2)
-                    + ((self.v.y * t + self.p.y) - (hailstone.v.y * t + hailstone.p.y)).powi(2)
-                    + ((self.v.z * t + self.p.z) - (hailstone.v.z * t + hailstone.p.z)).powi(2)
-            })
-            .sum()
+        })
     }
 
-    fn gradient(&self, hailstones: &[Hailstone]) -> Self {
-        let p = Dimensions {
-            x: self
-                .t
-                .iter()
-                .zip(hailstones)
-                .map(|(t, hailstone)| {
-                    2. * (self.v.x * t + self.p.x - (hailstone.v.x * t + hailstone.p.x))
-                })
-                .sum(),
-            y: self
-                .t
-                .iter()
-                .zip(hailstones)
-                .map(|(t, hailstone)| {
-                    2. * (self.v.y * t + self.p.y - (hailstone.v.y * t + hailstone.p.y))
-                })
-                .sum(),
-            z: self
-                .t
-                .iter()
-                .zip(hailstones)
-                .map(|(t, hailstone)| {
-                    2. * (self.v.z * t + self.p.z - (hailstone.v.z * t + hailstone.p.z))
-                })
-                .sum(),
-        };
-        let v = Dimensions {
-            x: self
-                .t
-                .iter()
-                .zip(hailstones)
-                .map(|(t, hailstone)| {
-                    2. * (self.v.x * t + self.p.x - (hailstone.v.x * t + hailstone.p.x)) * t
-                })
-                .sum(),
-            y: self
-                .t
-                .iter()
-                .zip(hailstones)
-                .map(|(t, hailstone)| {
-                    2. * (self.v.y * t + self.p.y - (hailstone.v.y * t + hailstone.p.y)) * t
-                })
-                .sum(),
-            z: self
-                .t
-                .iter()
-                .zip(hailstones)
-                .map(|(t, hailstone)| {
-                    2. * (self.v.z * t + self.p.z - (hailstone.v.z * t + hailstone.p.z)) * t
-                })
-                .sum(),
-        };
-        let t = self
-            .t
-            .iter()
-            .zip(hailstones)
-            .map(|(t, hailstone)| {
-                2. * (self.v.x * t + self.p.x - (hailstone.v.x * t + hailstone.p.x))
-                    * (self.v.x - hailstone.v.x)
-                    + 2. * (self.v.y * t + self.p.y - (hailstone.v.y * t + hailstone.p.y))
-                        * (self.v.y - hailstone.v.y)
-                    + 2. * (self.v.z * t + self.p.z - (hailstone.v.z * t + hailstone.p.z))
-                        * (self.v.z - hailstone.v.z)
-            })
-            .collect();
-        Self { p, v, t }
+    /// The time at which the rock hits the hailstone, if it does so at a
+    /// non-negative integer time.
+    fn hit_time(&self, hailstone: &Hailstone) -> Option<BigInt> {
+        let offset = hailstone.p.sub(&self.p);
+        let closing = self.v.sub(&hailstone.v);
+        let mut time: Option<BigRational> = None;
+        for (offset, closing) in offset.components().into_iter().zip(closing.components()) {
+            if closing.is_zero() {
+                if !offset.is_zero() {
+                    return None;
+                }
+            } else {
+                let t = offset / closing;
+                if time.as_ref().is_some_and(|time| *time != t) {
+                    return None;
+                }
+                time = Some(t);
+            }
+        }
+        let time = time.unwrap_or_else(BigRational::zero);
+        (time.is_integer() && !time.is_negative()).then(|| time.to_integer())
     }
 }
 
-impl SubAssign for Solution {
-    fn sub_assign(&mut self, rhs: Self) {
-        self.p -= rhs.p;
-        self.v -= rhs.v;
-        self.t.iter_mut().zip(rhs.t).for_each(|(t, rhs)| *t -= rhs);
-    }
-}
-
-impl Mul<f64> for Solution {
-    type Output = Self;
-
-    fn mul(self, rhs: f64) -> Self::Output {
-        Self {
-            p: self.p * rhs,
-            v: self.v * rhs,
-            t: self.t.into_iter().map(|t| t * rhs).collect(),
-        }
-    }
+#[test]
+fn test_rock_from_example() {
+    let hailstones: Vec<Hailstone> = [
+        "19, 13, 30 @ -2,  1, -2",
+        "18, 19, 22 @ -1, -1, -2",
+        "20, 25, 34 @ -2, -2, -4",
+        "12, 31, 28 @ -1, -2, -1",
+        "20, 19, 15 @  1, -5, -3",
+    ]
+    .iter()
+    .map(|line| line.parse().unwrap())
+    .collect();
+    let rock = Input(hailstones).rock().unwrap();
+    assert_eq!(rock.p, "24, 13, 10".parse().unwrap());
+    assert_eq!(rock.v, "-3, 1, 2".parse().unwrap());
 }
 
 struct Input(Vec<Hailstone>);
@@ -201,24 +199,35 @@ impl Input {
         )
     }
 
-    fn solve(&self) -> f64 {
-        let mut solution = Solution::new(&self.0);
-        let learning_rate = 0.001;
-        loop {
-            let error = solution.error(&self.0);
-            println!("{error}");
-            if error.is_nan() {
-                panic!()
-            }
-            if error < f64::EPSILON {
-                println!("{solution:#?}");
-                return (solution.p.x + solution.p.y + solution.p.z).round();
+    fn rock(&self) -> Result<Rock, String> {
+        let rock = self
+            .0
+            .iter()
+            .tuple_combinations()
+            .find_map(|(a, b, c)| Rock::from_triple(a, b, c))
+            .ok_or("No three hailstones determine a unique rock trajectory")?;
+        if !rock.p.is_integer() || !rock.v.is_integer() {
+            return Err(format!("Rock trajectory is not integral: {rock:?}"));
+        }
+        for (i, hailstone) in self.0.iter().enumerate() {
+            if rock.hit_time(hailstone).is_none() {
+                return Err(format!(
+                    "Rock {rock:?} does not hit hailstone {i} at a non-negative integer time"
+                ));
             }
-            solution -= solution.gradient(&self.0) * learning_rate;
         }
+        Ok(rock)
+    }
+
+    fn solve(&self) -> Result<BigInt, String> {
+        let rock = self.rock()?;
+        Ok((rock.p.x + rock.p.y + rock.p.z).to_integer())
     }
 }
 
 fn main() {
-    println!("{}", Input::parse().solve())
+    match Input::parse().solve() {
+        Ok(answer) => println!("{answer}"),
+        Err(error) => panic!("{error}"),
+    }
 }
@@ -1,4 +1,7 @@
-use std::{collections::VecDeque, io::stdin, str::FromStr};
+use std::{
+    collections::{BinaryHeap, HashMap as StdHashMap},
+    io::stdin,
+};
 
 use im::{HashMap, HashSet};
 use itertools::Itertools;
@@ -31,25 +34,6 @@ impl Graph {
         Self { nodes, edges }
     }
 
-    fn find_path(&self, start: Node, end: Node) -> Vec<Edge> {
-        let mut seen = HashSet::unit(start.clone());
-        let mut queue: VecDeque<(Node, Vec<Edge>)> = VecDeque::from([(start.clone(), vec![])]);
-        while let Some((node, path)) = queue.pop_front() {
-            for neighbor in self.edges.get(&node).unwrap() {
-                if !seen.contains(neighbor) {
-                    let mut new_path = path.clone();
-                    new_path.push((node.clone(), neighbor.clone()));
-                    if neighbor == &end {
-                        return new_path;
-                    }
-                    seen.insert(neighbor.clone());
-                    queue.push_back((neighbor.clone(), new_path));
-                }
-            }
-        }
-        panic!("No path found")
-    }
-
     fn remove_edge(&self, edge: Edge) -> Self {
         let nodes = self.nodes.clone();
         let edges = self
@@ -65,9 +49,9 @@ impl Graph {
         Self { nodes, edges }
     }
 
-    fn component_sizes(&self) -> Vec<usize> {
+    fn components(&self) -> Vec<Vec<Node>> {
         let mut unseen = self.nodes.clone();
-        let mut component_sizes = vec![];
+        let mut components = vec![];
         while let Some(start) = unseen.iter().next() {
             let mut seen = HashSet::unit(start.clone());
             let mut stack = vec![start.clone()];
@@ -79,10 +63,163 @@ impl Graph {
                     }
                 }
             }
-            component_sizes.push(seen.len());
+            components.push(seen.iter().cloned().sorted().collect());
             unseen = unseen.relative_complement(seen)
         }
-        component_sizes
+        components
+    }
+
+    /// Renders the wiring diagram in Graphviz DOT, drawing `highlighted` edges
+    /// in red.
+    fn to_dot(&self, highlighted: &[Edge]) -> String {
+        let is_highlighted = |a: &Node, b: &Node| {
+            highlighted
+                .iter()
+                .any(|(x, y)| (x == a && y == b) || (x == b && y == a))
+        };
+        let mut dot = "graph wiring {\n".to_owned();
+        for node in self.nodes.iter().sorted() {
+            for neighbor in self.edges.get(node).unwrap().iter().sorted() {
+                if node < neighbor {
+                    let style = if is_highlighted(node, neighbor) {
+                        " [color=red, penwidth=3]"
+                    } else {
+                        ""
+                    };
+                    dot.push_str(&format!("    {node} -- {neighbor}{style};\n"));
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Finds a minimum cut with the Stoer–Wagner algorithm, failing unless it
+    /// has exactly `target` edges.
+    fn min_cut(&self, target: usize) -> Result<Cut, String> {
+        let names: Vec<&Node> = self.nodes.iter().collect();
+        let index: StdHashMap<&Node, usize> =
+            names.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+        let mut weights: Vec<StdHashMap<usize, usize>> = names
+            .iter()
+            .map(|node| {
+                self.edges
+                    .get(*node)
+                    .into_iter()
+                    .flatten()
+                    .map(|neighbor| (index[neighbor], 1))
+                    .collect()
+            })
+            .collect();
+        let mut members: Vec<Vec<usize>> = (0..names.len()).map(|i| vec![i]).collect();
+        let mut active: Vec<usize> = (0..names.len()).collect();
+        let mut best: Option<(usize, Vec<usize>)> = None;
+        while active.len() > 1 {
+            let (s, t, cut_of_phase) = Self::minimum_cut_phase(&weights, &active);
+            if best
+                .as_ref()
+                .is_none_or(|(weight, _)| cut_of_phase < *weight)
+            {
+                best = Some((cut_of_phase, members[t].clone()));
+            }
+            let t_members = std::mem::take(&mut members[t]);
+            members[s].extend(t_members);
+            for (neighbor, weight) in std::mem::take(&mut weights[t]) {
+                weights[neighbor].remove(&t);
+                if neighbor != s {
+                    *weights[s].entry(neighbor).or_default() += weight;
+                    *weights[neighbor].entry(s).or_default() += weight;
+                }
+            }
+            active.retain(|&node| node != t);
+        }
+        let (weight, side) = best.ok_or("Graph has fewer than two nodes")?;
+        if weight != target {
+            return Err(format!("Minimum cut has {weight} edges, not {target}"));
+        }
+        let mut in_side = vec![false; names.len()];
+        for &node in &side {
+            in_side[node] = true;
+        }
+        let edges = self
+            .edges
+            .iter()
+            .flat_map(|(node, neighbors)| neighbors.iter().map(move |neighbor| (node, neighbor)))
+            .filter(|(node, neighbor)| in_side[index[node]] && !in_side[index[neighbor]])
+            .map(|(node, neighbor)| (node.clone(), neighbor.clone()))
+            .collect();
+        let (left, right) =
+            names
+                .iter()
+                .enumerate()
+                .fold((vec![], vec![]), |(mut left, mut right), (i, name)| {
+                    if in_side[i] {
+                        left.push((*name).clone());
+                    } else {
+                        right.push((*name).clone());
+                    }
+                    (left, right)
+                });
+        Ok(Cut {
+            edges,
+            partitions: (left, right),
+        })
+    }
+
+    /// Adds active nodes in maximum adjacency order, returning the last two
+    /// nodes added and the weight of the cut separating the last one.
+    fn minimum_cut_phase(
+        weights: &[StdHashMap<usize, usize>],
+        active: &[usize],
+    ) -> (usize, usize, usize) {
+        let mut connectivity = vec![0; weights.len()];
+        let mut added = vec![false; weights.len()];
+        let mut heap: BinaryHeap<(usize, usize)> = active.iter().map(|&node| (0, node)).collect();
+        let (mut s, mut t) = (active[0], active[0]);
+        while let Some((weight, node)) = heap.pop() {
+            if added[node] || weight != connectivity[node] {
+                continue;
+            }
+            added[node] = true;
+            (s, t) = (t, node);
+            for (&neighbor, &edge_weight) in &weights[node] {
+                if !added[neighbor] {
+                    connectivity[neighbor] += edge_weight;
+                    heap.push((connectivity[neighbor], neighbor));
+                }
+            }
+        }
+        (s, t, connectivity[t])
+    }
+}
+
+struct Cut {
+    edges: Vec<Edge>,
+    partitions: (Vec<Node>, Vec<Node>),
+}
+
+struct Options {
+    target: usize,
+    dot: bool,
+    components: bool,
+}
+
+impl Options {
+    /// Usage: `25_1 [--dot] [--components] [CUT_SIZE]`
+    fn parse() -> Self {
+        let mut options = Self {
+            target: 3,
+            dot: false,
+            components: false,
+        };
+        for arg in std::env::args().skip(1) {
+            match arg.as_str() {
+                "--dot" => options.dot = true,
+                "--components" => options.components = true,
+                _ => options.target = arg.parse().expect("Cut size must be an integer"),
+            }
+        }
+        options
     }
 }
 
@@ -90,52 +227,94 @@ struct Input(Graph);
 
 impl Input {
     fn parse() -> Self {
-        Self(
-            stdin()
-                .lines()
-                .map(|line| line.unwrap())
-                .fold(Graph::new(), |acc, line| {
-                    let (node, neighbors) = line.split_once(": ").unwrap();
-                    neighbors
-                        .split_ascii_whitespace()
-                        .fold(acc, |acc, neighbor| {
-                            acc.add_edge((node.to_owned(), neighbor.to_owned()))
-                        })
-                }),
-        )
+        Self::from_lines(stdin().lines().map(|line| line.unwrap()))
     }
 
-    fn solve(&self) -> usize {
-        for (start, end) in self
-            .0
-            .nodes
-            .iter()
-            .collect_vec()
+    fn from_lines(lines: impl Iterator<Item = String>) -> Self {
+        Self(lines.fold(Graph::new(), |acc, line| {
+            let (node, neighbors) = line.split_once(": ").unwrap();
+            neighbors
+                .split_ascii_whitespace()
+                .fold(acc, |acc, neighbor| {
+                    acc.add_edge((node.to_owned(), neighbor.to_owned()))
+                })
+        }))
+    }
+
+    fn solve(&self, options: &Options) -> Result<usize, String> {
+        let cut = self.0.min_cut(options.target)?;
+        if options.components {
+            eprintln!(
+                "Cut edges: {}",
+                cut.edges.iter().map(|(a, b)| format!("{a}/{b}")).join(", ")
+            );
+            eprintln!(
+                "Partitions: {} and {} nodes",
+                cut.partitions.0.len(),
+                cut.partitions.1.len()
+            );
+        }
+        if options.dot {
+            print!("{}", self.0.to_dot(&cut.edges));
+        }
+        let components = cut
+            .edges
             .into_iter()
-            .tuple_combinations()
-        {
-            let graph = self.0.clone();
-            let graph = graph
-                .find_path(start.clone(), end.clone())
-                .into_iter()
-                .fold(graph, |acc, edge| acc.remove_edge(edge));
-            let graph = graph
-                .find_path(start.clone(), end.clone())
-                .into_iter()
-                .fold(graph, |acc, edge| acc.remove_edge(edge));
-            let graph = graph
-                .find_path(start.clone(), end.clone())
-                .into_iter()
-                .fold(graph, |acc, edge| acc.remove_edge(edge));
-            let component_sizes = graph.component_sizes();
-            if component_sizes.len() == 2 {
-                return component_sizes[0] * component_sizes[1];
+            .fold(self.0.clone(), |acc, edge| acc.remove_edge(edge))
+            .components();
+        if options.components {
+            for component in &components {
+                eprintln!("{} nodes: {}", component.len(), component.join(" "));
             }
         }
-        panic!("No cut found")
+        if components.len() != 2 {
+            return Err(format!(
+                "Cutting left {} components, not 2",
+                components.len()
+            ));
+        }
+        Ok(components[0].len() * components[1].len())
     }
 }
 
+#[test]
+fn test_min_cut() {
+    let input = Input::from_lines(
+        [
+            "jqt: rhn xhk nvd",
+            "rsh: frs pzl lsr",
+            "xhk: hfx",
+            "cmg: qnr nvd lhk bvb",
+            "rhn: xhk bvb hfx",
+            "bvb: xhk hfx",
+            "pzl: lsr hfx nvd",
+            "qnr: nvd",
+            "ntq: jqt hfx bvb xhk",
+            "nvd: lhk",
+            "lsr: lhk",
+            "rzs: qnr cmg lsr rsh",
+            "frs: qnr lhk lsr",
+        ]
+        .into_iter()
+        .map(str::to_owned),
+    );
+    let cut = input.0.min_cut(3).unwrap();
+    assert_eq!(cut.edges.len(), 3);
+    let options = Options {
+        target: 3,
+        dot: false,
+        components: false,
+    };
+    assert_eq!(input.solve(&options), Ok(54));
+    assert!(input.0.min_cut(2).is_err());
+    assert!(input.0.min_cut(4).is_err());
+}
+
 fn main() {
-    println!("{}", Input::parse().solve())
+    let options = Options::parse();
+    match Input::parse().solve(&options) {
+        Ok(answer) if !options.dot => println!("{answer}"),
+        Ok(_) => {}
+        Err(error) => panic!("{error}"),
+    }
 }
@@ -0,0 +1,10 @@
+use advent_of_code_2023::nonogram::{render, Nonogram};
+use std::io::{read_to_string, stdin};
+
+fn main() {
+    let nonogram: Nonogram = read_to_string(stdin()).unwrap().parse().unwrap();
+    match nonogram.solve() {
+        Some(grid) => println!("{}", render(&grid)),
+        None => panic!("No solution"),
+    }
+}
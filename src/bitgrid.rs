@@ -0,0 +1,89 @@
+/// A grid of bits with each row packed into `u64` words, so whole rows can be
+/// compared with XOR and popcount.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        let words_per_row = width.div_ceil(64);
+        Self {
+            width,
+            height,
+            words_per_row,
+            words: vec![0; words_per_row * height],
+        }
+    }
+
+    pub fn from_rows(rows: &[Vec<bool>]) -> Self {
+        let mut grid = Self::new(rows.first().map_or(0, Vec::len), rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            for (x, &bit) in row.iter().enumerate() {
+                grid.set(y, x, bit);
+            }
+        }
+        grid
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, y: usize, x: usize) -> bool {
+        self.words[y * self.words_per_row + x / 64] >> (x % 64) & 1 == 1
+    }
+
+    pub fn set(&mut self, y: usize, x: usize, bit: bool) {
+        let word = &mut self.words[y * self.words_per_row + x / 64];
+        if bit {
+            *word |= 1 << (x % 64);
+        } else {
+            *word &= !(1 << (x % 64));
+        }
+    }
+
+    pub fn row(&self, y: usize) -> &[u64] {
+        &self.words[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+
+    /// The number of positions at which rows `a` and `b` differ.
+    pub fn row_distance(&self, a: usize, b: usize) -> usize {
+        self.row(a)
+            .iter()
+            .zip(self.row(b))
+            .map(|(a, b)| (a ^ b).count_ones() as usize)
+            .sum()
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut transposed = Self::new(self.height, self.width);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                transposed.set(x, y, self.get(y, x));
+            }
+        }
+        transposed
+    }
+}
+
+#[test]
+fn test_row_distance() {
+    let mut grid = BitGrid::new(130, 3);
+    grid.set(0, 3, true);
+    grid.set(0, 129, true);
+    grid.set(1, 3, true);
+    grid.set(2, 70, true);
+    assert_eq!(grid.row_distance(0, 1), 1);
+    assert_eq!(grid.row_distance(0, 2), 3);
+    let transposed = grid.transpose();
+    assert_eq!((transposed.width(), transposed.height()), (3, 130));
+    assert!(transposed.get(129, 0) && !transposed.get(129, 1));
+}
@@ -0,0 +1,63 @@
+/// The shape of the sequence `start, step(start), step(step(start)), ...`:
+/// after `pre_period` steps it repeats every `period` steps.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Cycle {
+    pub pre_period: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// Finds the cycle with Brent's algorithm, which holds only two states at
+    /// a time.
+    pub fn find<T: Clone + PartialEq>(start: &T, mut step: impl FnMut(&T) -> T) -> Self {
+        let mut power = 1;
+        let mut period = 1;
+        let mut tortoise = start.clone();
+        let mut hare = step(start);
+        while tortoise != hare {
+            if power == period {
+                tortoise = hare.clone();
+                power *= 2;
+                period = 0;
+            }
+            hare = step(&hare);
+            period += 1;
+        }
+        let mut tortoise = start.clone();
+        let mut hare = start.clone();
+        for _ in 0..period {
+            hare = step(&hare);
+        }
+        let mut pre_period = 0;
+        while tortoise != hare {
+            tortoise = step(&tortoise);
+            hare = step(&hare);
+            pre_period += 1;
+        }
+        Self { pre_period, period }
+    }
+
+    /// The smallest number of steps that reaches the same state as `n` steps.
+    pub fn equivalent_steps(&self, n: usize) -> usize {
+        if n < self.pre_period {
+            n
+        } else {
+            self.pre_period + (n - self.pre_period) % self.period
+        }
+    }
+}
+
+#[test]
+fn test_find() {
+    let step = |x: &usize| if *x < 5 { x + 1 } else { 2 };
+    let cycle = Cycle::find(&0, step);
+    assert_eq!(
+        cycle,
+        Cycle {
+            pre_period: 2,
+            period: 4,
+        }
+    );
+    assert_eq!(cycle.equivalent_steps(1), 1);
+    assert_eq!(cycle.equivalent_steps(11), 3);
+}
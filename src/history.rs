@@ -0,0 +1,145 @@
+use itertools::Itertools;
+use num::{BigInt, One, Zero};
+use std::str::FromStr;
+
+pub struct History(pub Vec<i64>);
+
+impl FromStr for History {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(
+            s.split_whitespace().map(|s| s.parse().unwrap()).collect(),
+        ))
+    }
+}
+
+impl History {
+    fn derivative(&self) -> Self {
+        Self(self.0.iter().tuple_windows().map(|(a, b)| b - a).collect())
+    }
+
+    /// The history followed by each successive derivative, stopping at the
+    /// first all-zero row.
+    pub fn difference_table(&self) -> Vec<Vec<i64>> {
+        let mut table = vec![self.0.clone()];
+        while table.last().unwrap().iter().any(|v| *v != 0) {
+            let derivative = Self(table.last().unwrap().clone()).derivative();
+            table.push(derivative.0);
+        }
+        table
+    }
+
+    /// The degree of the polynomial generating the history, which is only
+    /// known once the difference table reaches a non-empty all-zero row.
+    pub fn degree(&self) -> Result<usize, String> {
+        self.degree_of(&self.difference_table())
+    }
+
+    fn degree_of(&self, table: &[Vec<i64>]) -> Result<usize, String> {
+        if table.last().unwrap().is_empty() {
+            return Err(format!(
+                "History of length {} never reaches an all-zero difference row: its degree \
+                 is at least {}, too high to determine from so few values",
+                self.0.len(),
+                self.0.len().saturating_sub(1)
+            ));
+        }
+        Ok(table.len().saturating_sub(2))
+    }
+
+    /// The value at `index`, where 0 is the first element, by Newton's forward
+    /// difference formula: the sum of `Δᵏ(0) * C(index, k)`.
+    pub fn value_at(&self, index: &BigInt) -> Result<BigInt, String> {
+        let table = self.difference_table();
+        let degree = self.degree_of(&table)?;
+        let mut binomial = BigInt::one();
+        let mut value = BigInt::zero();
+        for (k, row) in table.iter().take(degree + 1).enumerate() {
+            value += &binomial * row[0];
+            binomial = binomial * (index - k) / (k + 1);
+        }
+        Ok(value)
+    }
+
+    /// Extrapolates `steps` past the last element, or before the first if
+    /// `steps` is negative.
+    pub fn extrapolate(&self, steps: i64) -> Result<BigInt, String> {
+        let index = if steps < 0 {
+            steps
+        } else {
+            self.0.len() as i64 - 1 + steps
+        };
+        self.value_at(&index.into())
+    }
+
+    /// The difference table indented as in the puzzle text, followed by the
+    /// degree or the reason it is unknown.
+    pub fn render_table(&self) -> String {
+        let table = self.difference_table();
+        let mut lines = table
+            .iter()
+            .enumerate()
+            .map(|(depth, row)| format!("{}{}", "  ".repeat(depth), row.iter().join("   ")))
+            .collect_vec();
+        lines.push(match self.degree_of(&table) {
+            Ok(degree) => format!("degree {degree}"),
+            Err(error) => error,
+        });
+        lines.join("\n")
+    }
+}
+
+/// Sums the extrapolations of every history, naming the first line that
+/// fails.
+pub fn total_extrapolated(histories: &[History], steps: i64) -> Result<BigInt, String> {
+    histories
+        .iter()
+        .enumerate()
+        .map(|(i, history)| {
+            history
+                .extrapolate(steps)
+                .map_err(|error| format!("Line {}: {error}", i + 1))
+        })
+        .sum()
+}
+
+/// Command line options: `[STEPS] [--table]`.
+pub struct Options {
+    pub steps: i64,
+    pub table: bool,
+}
+
+impl Options {
+    pub fn parse(default_steps: i64) -> Self {
+        let mut options = Self {
+            steps: default_steps,
+            table: false,
+        };
+        for arg in std::env::args().skip(1) {
+            match arg.as_str() {
+                "--table" => options.table = true,
+                _ => options.steps = arg.parse().expect("Steps must be an integer"),
+            }
+        }
+        options
+    }
+}
+
+#[test]
+fn test_extrapolate() {
+    let history: History = "0 3 6 9 12 15".parse().unwrap();
+    assert_eq!(history.extrapolate(1), Ok(18.into()));
+    assert_eq!(history.extrapolate(-2), Ok((-6).into()));
+    let squares: History = "0 1 4 9".parse().unwrap();
+    assert_eq!(squares.degree(), Ok(2));
+    let far = BigInt::from(10).pow(12);
+    assert_eq!(squares.value_at(&far), Ok(far.pow(2)));
+    let short: History = "1 2 4".parse().unwrap();
+    assert!(short.degree().is_err());
+    assert!(short.value_at(&far).is_err());
+    assert_eq!(
+        total_extrapolated(&[history, squares], -1),
+        Ok((-3 + 1).into())
+    );
+}
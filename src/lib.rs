@@ -0,0 +1,7 @@
+pub mod bitgrid;
+pub mod cycle;
+pub mod history;
+pub mod mirrors;
+pub mod node_pattern;
+pub mod nonogram;
+pub mod springs;
@@ -0,0 +1,235 @@
+use crate::bitgrid::BitGrid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reflection {
+    Row(usize),
+    Col(usize),
+}
+
+impl Reflection {
+    pub fn value(&self) -> usize {
+        match self {
+            Reflection::Row(r) => 100 * *r,
+            Reflection::Col(c) => *c,
+        }
+    }
+}
+
+/// A pattern stored both row-wise and column-wise, so that checking either
+/// kind of reflection compares packed rows.
+pub struct Pattern {
+    rows: BitGrid,
+    cols: BitGrid,
+}
+
+impl Pattern {
+    pub fn parse(lines: impl Iterator<Item = String>) -> Vec<Self> {
+        let mut patterns: Vec<Pattern> = vec![];
+        let mut current_pattern: Vec<Vec<bool>> = vec![];
+        for line in lines {
+            if line.is_empty() {
+                patterns.push(Pattern::new(&current_pattern));
+                current_pattern = vec![];
+            } else {
+                current_pattern.push(
+                    line.chars()
+                        .map(|c| match c {
+                            '.' => false,
+                            '#' => true,
+                            _ => panic!("Invalid character"),
+                        })
+                        .collect(),
+                );
+            }
+        }
+        patterns.push(Pattern::new(&current_pattern));
+        patterns
+    }
+
+    pub fn new(cells: &[Vec<bool>]) -> Self {
+        let rows = BitGrid::from_rows(cells);
+        let cols = rows.transpose();
+        Self { rows, cols }
+    }
+
+    /// Every reflection line that needs exactly `smudges` cells fixed.
+    pub fn reflections(&self, smudges: usize) -> Vec<Reflection> {
+        let rows = (1..self.rows.height())
+            .filter(|&r| mirror_mismatches(&self.rows, r, smudges) == Some(smudges))
+            .map(Reflection::Row);
+        let cols = (1..self.cols.height())
+            .filter(|&c| mirror_mismatches(&self.cols, c, smudges) == Some(smudges))
+            .map(Reflection::Col);
+        rows.chain(cols).collect()
+    }
+}
+
+/// The number of cells that differ from their mirror image across the line
+/// between rows `axis - 1` and `axis`, or `None` once it exceeds `limit`.
+fn mirror_mismatches(grid: &BitGrid, axis: usize, limit: usize) -> Option<usize> {
+    let mut mismatches = 0;
+    for (a, b) in (0..axis).rev().zip(axis..grid.height()) {
+        mismatches += grid.row_distance(a, b);
+        if mismatches > limit {
+            return None;
+        }
+    }
+    Some(mismatches)
+}
+
+impl Pattern {
+    /// The cells before the reflection line that differ from their mirror
+    /// image, i.e. the smudges to fix.
+    pub fn smudges(&self, reflection: Reflection) -> Vec<(usize, usize)> {
+        let mut smudges = vec![];
+        for y in 0..self.rows.height() {
+            for x in 0..self.rows.width() {
+                let mirror = match reflection {
+                    Reflection::Row(r) if y < r => Some((2 * r - y - 1, x)),
+                    Reflection::Col(c) if x < c => Some((y, 2 * c - x - 1)),
+                    _ => None,
+                };
+                if mirror.is_some_and(|(my, mx)| {
+                    my < self.rows.height()
+                        && mx < self.rows.width()
+                        && self.rows.get(my, mx) != self.rows.get(y, x)
+                }) {
+                    smudges.push((y, x));
+                }
+            }
+        }
+        smudges
+    }
+
+    /// Draws the pattern with its smudges fixed, and the reflection line
+    /// marked with `><` or `v^` as in the puzzle text. Fixed smudges are shown
+    /// in reverse video if `ansi` is set, and listed below the pattern if not.
+    pub fn render(&self, reflection: Reflection, ansi: bool) -> String {
+        let smudges = self.smudges(reflection);
+        let cells = |y: usize| -> String {
+            (0..self.rows.width())
+                .map(|x| {
+                    let cell = self.rows.get(y, x);
+                    let fixed = smudges.contains(&(y, x));
+                    let c = if cell != fixed { '#' } else { '.' };
+                    if fixed && ansi {
+                        format!("\x1b[7m{c}\x1b[0m")
+                    } else {
+                        c.to_string()
+                    }
+                })
+                .collect()
+        };
+        let mut lines = vec![];
+        match reflection {
+            Reflection::Row(r) => {
+                let width = self.rows.height().to_string().len();
+                for y in 0..self.rows.height() {
+                    let marker = match y + 1 {
+                        n if n == r => 'v',
+                        n if n == r + 1 => '^',
+                        _ => ' ',
+                    };
+                    lines.push(format!(
+                        "{:>width$}{marker}{}{marker}{}",
+                        y + 1,
+                        cells(y),
+                        y + 1
+                    ));
+                }
+            }
+            Reflection::Col(c) => {
+                let numbers: String = (1..=self.rows.width())
+                    .map(|x| char::from_digit((x % 10) as u32, 10).unwrap())
+                    .collect();
+                let markers: String = (1..=self.rows.width())
+                    .map(|x| match x {
+                        x if x == c => '>',
+                        x if x == c + 1 => '<',
+                        _ => ' ',
+                    })
+                    .collect();
+                lines.push(numbers.clone());
+                lines.push(markers.clone());
+                lines.extend((0..self.rows.height()).map(cells));
+                lines.push(markers);
+                lines.push(numbers);
+            }
+        }
+        if !ansi {
+            lines.extend(
+                smudges
+                    .iter()
+                    .map(|(y, x)| format!("Smudge fixed at row {}, column {}", y + 1, x + 1)),
+            );
+        }
+        lines.join("\n")
+    }
+}
+
+/// Sums the value of each pattern's reflection line, failing unless every
+/// pattern has exactly one.
+pub fn summarize(patterns: &[Pattern], smudges: usize) -> Result<usize, String> {
+    patterns
+        .iter()
+        .enumerate()
+        .map(|(i, pattern)| match pattern.reflections(smudges)[..] {
+            [reflection] => Ok(reflection.value()),
+            [] => Err(format!(
+                "Pattern {} has no reflection with {smudges} smudges",
+                i + 1
+            )),
+            ref reflections => Err(format!(
+                "Pattern {} has {} reflections with {smudges} smudges: {reflections:?}",
+                i + 1,
+                reflections.len()
+            )),
+        })
+        .sum()
+}
+
+#[test]
+fn test_reflections() {
+    let patterns = Pattern::parse(
+        [
+            "#.##..##.",
+            "..#.##.#.",
+            "##......#",
+            "##......#",
+            "..#.##.#.",
+            "..##..##.",
+            "#.#.##.#.",
+            "",
+            "#...##..#",
+            "#....#..#",
+            "..##..###",
+            "#####.##.",
+            "#####.##.",
+            "..##..###",
+            "#....#..#",
+        ]
+        .into_iter()
+        .map(str::to_owned),
+    );
+    assert_eq!(patterns[0].reflections(0), [Reflection::Col(5)]);
+    assert_eq!(patterns[1].reflections(0), [Reflection::Row(4)]);
+    assert_eq!(patterns[0].reflections(1), [Reflection::Row(3)]);
+    assert_eq!(patterns[1].reflections(1), [Reflection::Row(1)]);
+    assert_eq!(summarize(&patterns, 0), Ok(405));
+    assert_eq!(summarize(&patterns, 1), Ok(400));
+    assert!(summarize(&patterns, 100).is_err());
+    let symmetric = Pattern::parse(["##", "##"].into_iter().map(str::to_owned));
+    assert_eq!(
+        symmetric[0].reflections(0),
+        [Reflection::Row(1), Reflection::Col(1)]
+    );
+    assert!(summarize(&symmetric, 0).is_err());
+    assert_eq!(patterns[0].smudges(Reflection::Row(3)), [(0, 0)]);
+    assert_eq!(patterns[1].smudges(Reflection::Row(1)), [(0, 4)]);
+    let plain = patterns[1].render(Reflection::Row(1), false);
+    assert!(!plain.contains('\x1b'));
+    assert!(plain.ends_with("Smudge fixed at row 1, column 5"));
+    assert!(patterns[1]
+        .render(Reflection::Row(1), true)
+        .contains("\x1b[7m"));
+}
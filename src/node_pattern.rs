@@ -0,0 +1,79 @@
+use regex::Regex;
+use std::str::FromStr;
+
+/// Selects start or goal nodes: `exact:NAME`, `suffix:SUFFIX` or
+/// `regex:REGEX`, where an unanchored regex matches anywhere in the name.
+pub enum NodePattern {
+    Exact(String),
+    Suffix(String),
+    Regex(Regex),
+}
+
+impl FromStr for NodePattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some(("exact", name)) => Ok(Self::Exact(name.to_owned())),
+            Some(("suffix", suffix)) => Ok(Self::Suffix(suffix.to_owned())),
+            Some(("regex", regex)) => Regex::new(regex)
+                .map(Self::Regex)
+                .map_err(|error| error.to_string()),
+            _ => Err(format!(
+                "Invalid pattern {s}, expected exact:NAME, suffix:SUFFIX or regex:REGEX"
+            )),
+        }
+    }
+}
+
+impl NodePattern {
+    pub fn matches(&self, node: &str) -> bool {
+        match self {
+            NodePattern::Exact(name) => node == name,
+            NodePattern::Suffix(suffix) => node.ends_with(suffix.as_str()),
+            NodePattern::Regex(regex) => regex.is_match(node),
+        }
+    }
+}
+
+/// The start and goal patterns given by `--start PATTERN` and `--goal PATTERN`.
+pub struct Endpoints {
+    pub start: NodePattern,
+    pub goal: NodePattern,
+}
+
+impl Endpoints {
+    /// Parses the command line, using `start` and `goal` for missing options.
+    pub fn parse(start: NodePattern, goal: NodePattern) -> Self {
+        let mut endpoints = Self { start, goal };
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let pattern = args
+                .next()
+                .unwrap_or_else(|| panic!("{arg} needs a pattern"))
+                .parse()
+                .unwrap_or_else(|error| panic!("{error}"));
+            match arg.as_str() {
+                "--start" => endpoints.start = pattern,
+                "--goal" => endpoints.goal = pattern,
+                _ => panic!("Unknown argument {arg}"),
+            }
+        }
+        endpoints
+    }
+}
+
+#[test]
+fn test_node_pattern() {
+    let pattern = |s: &str| -> NodePattern { s.parse().unwrap() };
+    assert!(pattern("exact:AAA").matches("AAA"));
+    assert!(!pattern("exact:AAA").matches("BAAA"));
+    assert!(pattern("suffix:A").matches("11A"));
+    assert!(pattern("regex:^1.*A$").matches("12BA"));
+    assert!(!pattern("regex:^1.*A$").matches("21BA"));
+    assert!(pattern("regex:Z").matches("1Z2"));
+    assert!(pattern("regex:[AB]Z").matches("1BZ"));
+    assert!(pattern("regex:^(AA|BB)+C?$").matches("AABB"));
+    assert!("regex:[A".parse::<NodePattern>().is_err());
+    assert!("prefix:A".parse::<NodePattern>().is_err());
+}
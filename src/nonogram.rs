@@ -0,0 +1,147 @@
+use itertools::Itertools;
+use std::str::FromStr;
+
+use crate::springs::{ArrangementTable, Spring};
+
+/// A 2D nonogram: each row and column is a day 12 spring line whose groups
+/// are its clue.
+pub struct Nonogram {
+    rows: Vec<Vec<usize>>,
+    columns: Vec<Vec<usize>>,
+}
+
+/// Parses row clues, a blank line, then column clues, one comma-separated clue
+/// per line. A line with no filled cells has the clue `0` on its own, since an
+/// empty line would read as the separator.
+impl FromStr for Nonogram {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_clues = |block: &str| -> Result<Vec<Vec<usize>>, ()> {
+            block
+                .lines()
+                .map(|line| {
+                    if line.trim() == "0" {
+                        return Ok(vec![]);
+                    }
+                    line.split(',')
+                        .map(|group| match group.trim().parse() {
+                            Ok(0) | Err(_) => Err(()),
+                            Ok(size) => Ok(size),
+                        })
+                        .collect()
+                })
+                .collect()
+        };
+        let (rows, columns) = s.trim_end_matches('\n').split_once("\n\n").ok_or(())?;
+        Ok(Self {
+            rows: parse_clues(rows)?,
+            columns: parse_clues(columns)?,
+        })
+    }
+}
+
+type Grid = Vec<Vec<Spring>>;
+
+impl Nonogram {
+    pub fn solve(&self) -> Option<Vec<Vec<bool>>> {
+        let grid = vec![vec![None; self.columns.len()]; self.rows.len()];
+        let grid = self.search(grid, &mut ArrangementTable::default())?;
+        Some(
+            grid.into_iter()
+                .map(|row| row.into_iter().map(Option::unwrap).collect())
+                .collect(),
+        )
+    }
+
+    /// Fixes forced cells, then guesses the first unknown cell and recurses.
+    fn search(&self, mut grid: Grid, table: &mut ArrangementTable) -> Option<Grid> {
+        self.propagate(&mut grid, table)?;
+        let Some((y, x)) = (0..grid.len())
+            .cartesian_product(0..self.columns.len())
+            .find(|&(y, x)| grid[y][x].is_none())
+        else {
+            return Some(grid);
+        };
+        [true, false].into_iter().find_map(|guess| {
+            let mut grid = grid.clone();
+            grid[y][x] = Some(guess);
+            self.search(grid, table)
+        })
+    }
+
+    /// Repeatedly solves every row and column until nothing changes, failing
+    /// if some line has no valid arrangement.
+    fn propagate(&self, grid: &mut Grid, table: &mut ArrangementTable) -> Option<()> {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (row, groups) in grid.iter_mut().zip(&self.rows) {
+                changed |= fix_forced(row, groups, table)?;
+            }
+            for (x, groups) in self.columns.iter().enumerate() {
+                let mut column = grid.iter().map(|row| row[x]).collect_vec();
+                if fix_forced(&mut column, groups, table)? {
+                    changed = true;
+                    for (row, cell) in grid.iter_mut().zip(column) {
+                        row[x] = cell;
+                    }
+                }
+            }
+        }
+        Some(())
+    }
+}
+
+/// Fixes each unknown cell that takes the same value in every arrangement of
+/// the line, returning whether any cell was fixed.
+fn fix_forced(line: &mut [Spring], groups: &[usize], table: &mut ArrangementTable) -> Option<bool> {
+    let mut count = |line: &[Spring]| {
+        table.fill(line, groups);
+        table.get(0, 0)
+    };
+    if count(line) == 0 {
+        return None;
+    }
+    let mut changed = false;
+    for i in 0..line.len() {
+        if line[i].is_some() {
+            continue;
+        }
+        line[i] = Some(true);
+        let damaged = count(line);
+        line[i] = Some(false);
+        let undamaged = count(line);
+        line[i] = match (damaged, undamaged) {
+            (0, _) => Some(false),
+            (_, 0) => Some(true),
+            _ => None,
+        };
+        changed |= line[i].is_some();
+    }
+    Some(changed)
+}
+
+pub fn render(grid: &[Vec<bool>]) -> String {
+    grid.iter()
+        .map(|row| {
+            row.iter()
+                .map(|&cell| if cell { '#' } else { '.' })
+                .join("")
+        })
+        .join("\n")
+}
+
+#[test]
+fn test_solve() {
+    let nonogram: Nonogram = "1\n3\n1,1\n\n2\n2\n2".parse().unwrap();
+    assert_eq!(render(&nonogram.solve().unwrap()), ".#.\n###\n#.#");
+    let ambiguous: Nonogram = "1\n1\n\n1\n1".parse().unwrap();
+    assert_eq!(render(&ambiguous.solve().unwrap()), "#.\n.#");
+    let impossible: Nonogram = "2\n\n1\n1\n1".parse().unwrap();
+    assert!(impossible.solve().is_none());
+    let empty_row: Nonogram = "0\n3\n1,1\n\n2\n1\n2".parse().unwrap();
+    assert_eq!(render(&empty_row.solve().unwrap()), "...\n###\n#.#");
+    assert!("1\n\n1\n\n1\n0\n1".parse::<Nonogram>().is_err());
+    assert!("1,0,2\n\n1\n1\n1\n1".parse::<Nonogram>().is_err());
+}
@@ -0,0 +1,227 @@
+use itertools::Itertools;
+use std::{iter::repeat_n, str::FromStr, thread};
+
+pub type Spring = Option<bool>;
+
+pub fn falsable(spring: &Spring) -> bool {
+    !spring.unwrap_or(false)
+}
+
+pub fn truable(spring: &Spring) -> bool {
+    spring.unwrap_or(true)
+}
+
+/// Bottom-up counts of arrangements, indexed by (spring offset, group index):
+/// the entry for `(i, g)` counts the arrangements of `springs[i..]` into
+/// `groups[g..]`. Buffers are reused when the table is refilled.
+#[derive(Default)]
+pub struct ArrangementTable {
+    counts: Vec<usize>,
+    /// Number of consecutive springs from each offset that could be damaged.
+    truable_runs: Vec<usize>,
+    width: usize,
+}
+
+impl ArrangementTable {
+    pub fn fill(&mut self, springs: &[Spring], groups: &[usize]) {
+        let (n, m) = (springs.len(), groups.len());
+        self.width = m + 1;
+        self.counts.clear();
+        self.counts.resize((n + 1) * self.width, 0);
+        self.truable_runs.clear();
+        self.truable_runs.resize(n + 1, 0);
+        for i in (0..n).rev() {
+            if truable(&springs[i]) {
+                self.truable_runs[i] = self.truable_runs[i + 1] + 1;
+            }
+        }
+        self.counts[n * self.width + m] = 1;
+        for i in (0..n).rev() {
+            if falsable(&springs[i]) {
+                self.counts[i * self.width + m] = self.get(i + 1, m);
+            }
+            for (g, &size) in groups.iter().enumerate().rev() {
+                let undamaged = if falsable(&springs[i]) {
+                    self.get(i + 1, g)
+                } else {
+                    0
+                };
+                let damaged = if self.truable_runs[i] < size {
+                    0
+                } else if i + size == n {
+                    self.get(n, g + 1)
+                } else if falsable(&springs[i + size]) {
+                    self.get(i + size + 1, g + 1)
+                } else {
+                    0
+                };
+                self.counts[i * self.width + g] = undamaged + damaged;
+            }
+        }
+    }
+
+    pub fn get(&self, offset: usize, group: usize) -> usize {
+        self.counts[offset * self.width + group]
+    }
+}
+
+pub struct Row {
+    pub springs: Vec<Spring>,
+    pub groups: Vec<usize>,
+}
+
+impl FromStr for Row {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (springs_s, groups_s) = s.split_once(' ').unwrap();
+        let springs = springs_s
+            .chars()
+            .map(|c| match c {
+                '.' => Some(false),
+                '#' => Some(true),
+                '?' => None,
+                _ => panic!(),
+            })
+            .collect();
+        let groups = groups_s
+            .split(',')
+            .map(|group_s| usize::from_str(group_s).unwrap())
+            .collect();
+        Ok(Self { springs, groups })
+    }
+}
+
+impl Row {
+    /// Repeats the springs `factor` times separated by unknown springs, and the
+    /// groups `factor` times.
+    pub fn unfold(&self, factor: usize) -> Self {
+        let springs = Itertools::intersperse(repeat_n(self.springs.clone(), factor), vec![None])
+            .flatten()
+            .collect();
+        let groups = repeat_n(self.groups.clone(), factor).flatten().collect();
+        Self { springs, groups }
+    }
+
+    pub fn count_with(&self, table: &mut ArrangementTable) -> usize {
+        table.fill(&self.springs, &self.groups);
+        table.get(0, 0)
+    }
+
+    pub fn count(&self) -> usize {
+        self.count_with(&mut ArrangementTable::default())
+    }
+
+    /// Lists every arrangement as a `#`/`.` string, using the table to skip
+    /// branches with no arrangements.
+    pub fn arrangements(&self) -> Arrangements<'_> {
+        let mut table = ArrangementTable::default();
+        table.fill(&self.springs, &self.groups);
+        Arrangements {
+            row: self,
+            table,
+            stack: vec![(0, 0, String::new())],
+        }
+    }
+}
+
+pub struct Arrangements<'a> {
+    row: &'a Row,
+    table: ArrangementTable,
+    /// Spring offset, group index, and the arrangement of the springs before
+    /// the offset.
+    stack: Vec<(usize, usize, String)>,
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        while let Some((offset, group, prefix)) = self.stack.pop() {
+            if self.table.get(offset, group) == 0 {
+                continue;
+            }
+            let springs = &self.row.springs[offset..];
+            let Some(&size) = self.row.groups.get(group) else {
+                return Some(prefix + &".".repeat(springs.len()));
+            };
+            if springs.len() == size {
+                return Some(prefix + &"#".repeat(size));
+            }
+            if falsable(&springs[0]) {
+                self.stack.push((offset + 1, group, prefix.clone() + "."));
+            }
+            if springs[..size].iter().all(truable) && falsable(&springs[size]) {
+                self.stack.push((
+                    offset + size + 1,
+                    group + 1,
+                    prefix + &"#".repeat(size) + ".",
+                ));
+            }
+        }
+        None
+    }
+}
+
+/// Sums the arrangement counts of every row unfolded by `factor`, splitting
+/// the rows across threads that each reuse one table.
+pub fn total_arrangements(rows: &[Row], factor: usize) -> usize {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = rows.len().div_ceil(threads).max(1);
+    thread::scope(|scope| {
+        rows.chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let mut table = ArrangementTable::default();
+                    chunk
+                        .iter()
+                        .map(|row| row.unfold(factor).count_with(&mut table))
+                        .sum::<usize>()
+                })
+            })
+            .collect_vec()
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .sum()
+    })
+}
+
+#[test]
+fn test_against_brute_force() {
+    for line in [
+        "???.### 1,1,3",
+        ".??..??...?##. 1,1,3",
+        "?#?#?#?#?#?#?#? 1,3,1,6",
+        "????.#...#... 4,1,1",
+        "????.######..#####. 1,6,5",
+        "?###???????? 3,2,1",
+    ] {
+        let row: Row = line.parse().unwrap();
+        for row in [row.unfold(1), row.unfold(2)] {
+            let unknowns = row.springs.iter().positions(Option::is_none).collect_vec();
+            let brute_force = (0..1u32 << unknowns.len())
+                .map(|bits| {
+                    let mut springs = row.springs.clone();
+                    for (bit, &i) in unknowns.iter().enumerate() {
+                        springs[i] = Some(bits >> bit & 1 == 1);
+                    }
+                    springs
+                        .iter()
+                        .map(|spring| if *spring == Some(true) { '#' } else { '.' })
+                        .collect::<String>()
+                })
+                .filter(|arrangement| {
+                    let groups = arrangement
+                        .split('.')
+                        .filter(|group| !group.is_empty())
+                        .map(str::len)
+                        .collect_vec();
+                    groups == row.groups
+                })
+                .sorted()
+                .collect_vec();
+            assert_eq!(row.count(), brute_force.len());
+            assert_eq!(row.arrangements().sorted().collect_vec(), brute_force);
+        }
+    }
+}